@@ -0,0 +1,209 @@
+// Frame animations and tweens. Both advance one simulation update at a time
+// instead of following the wall clock, so replays and headless runs see
+// exactly the same values.
+
+// Shape of a tween over time. `apply` maps progress 0..1 to eased progress.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    SineInOut,
+    // Overshoots the target a little before settling.
+    BackOut,
+}
+
+impl Easing {
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 { 2.0 * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0 }
+            },
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::SineInOut => (1.0 - (t * std::f64::consts::PI).cos()) / 2.0,
+            Easing::BackOut => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            },
+        }
+    }
+}
+
+// Values a tween can move between.
+pub trait Lerp: Copy {
+    fn lerp(self, to: Self, t: f64) -> Self;
+}
+
+impl Lerp for f64 {
+    fn lerp(self, to: f64, t: f64) -> f64 {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: f32, t: f64) -> f32 {
+        self + (to - self) * t as f32
+    }
+}
+
+// Positions and scales.
+impl Lerp for [f64; 2] {
+    fn lerp(self, to: [f64; 2], t: f64) -> [f64; 2] {
+        [self[0].lerp(to[0], t), self[1].lerp(to[1], t)]
+    }
+}
+
+// Colours.
+impl Lerp for [f32; 4] {
+    fn lerp(self, to: [f32; 4], t: f64) -> [f32; 4] {
+        [0, 1, 2, 3].map(|i| self[i].lerp(to[i], t))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tween<T: Lerp> {
+    pub from: T,
+    pub to: T,
+    // Length in updates.
+    pub duration: u32,
+    pub easing: Easing,
+    elapsed: u32,
+}
+
+impl<T: Lerp> Tween<T> {
+    pub fn new(from: T, to: T, duration: u32, easing: Easing) -> Tween<T> {
+        Tween { from, to, duration, easing, elapsed: 0 }
+    }
+
+    // A tween that already sits at `value`.
+    pub fn at(value: T) -> Tween<T> {
+        Tween::new(value, value, 0, Easing::Linear)
+    }
+
+    pub fn step(&mut self) {
+        self.elapsed = (self.elapsed + 1).min(self.duration);
+    }
+
+    pub fn restart(&mut self) {
+        self.elapsed = 0;
+    }
+
+    pub fn progress(&self) -> f64 {
+        if self.duration == 0 { 1.0 } else { f64::from(self.elapsed) / f64::from(self.duration) }
+    }
+
+    pub fn value(&self) -> T {
+        self.from.lerp(self.to, self.easing.apply(self.progress()))
+    }
+
+    pub fn done(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Playback {
+    #[default]
+    Loop,
+    // Stop on the last frame.
+    Once,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    // Sprite shown during this frame.
+    pub sprite: String,
+    // How many updates the frame is shown for.
+    pub duration: u32,
+}
+
+impl Frame {
+    pub fn new(sprite: &str, duration: u32) -> Frame {
+        Frame { sprite: sprite.to_string(), duration }
+    }
+}
+
+pub struct Animation {
+    pub frames: Vec<Frame>,
+    pub playback: Playback,
+    current: usize,
+    // Updates spent on the current frame.
+    elapsed: u32,
+    finished: bool,
+    on_finish: Vec<Box<dyn FnMut()>>,
+}
+
+impl Animation {
+    pub fn new(frames: Vec<Frame>, playback: Playback) -> Animation {
+        Animation {
+            frames,
+            playback,
+            current: 0,
+            elapsed: 0,
+            finished: false,
+            on_finish: Vec::new(),
+        }
+    }
+
+    // Call `callback` when a `Once` animation reaches its end, or every time
+    // a looping one wraps around.
+    pub fn on_finish(mut self, callback: Box<dyn FnMut()>) -> Animation {
+        self.on_finish.push(callback);
+        self
+    }
+
+    // Advance by one update. Returns true if the animation finished or looped.
+    pub fn step(&mut self) -> bool {
+        if self.finished || self.frames.is_empty() {
+            return false;
+        }
+
+        self.elapsed += 1;
+        if self.elapsed < self.frames[self.current].duration.max(1) {
+            return false;
+        }
+
+        self.elapsed = 0;
+        if self.current + 1 < self.frames.len() {
+            self.current += 1;
+            return false;
+        }
+
+        match self.playback {
+            Playback::Loop => self.current = 0,
+            Playback::Once => self.finished = true,
+        }
+        for callback in self.on_finish.iter_mut() {
+            callback();
+        }
+        true
+    }
+
+    pub fn restart(&mut self) {
+        self.current = 0;
+        self.elapsed = 0;
+        self.finished = false;
+    }
+
+    pub fn index(&self) -> usize {
+        self.current
+    }
+
+    pub fn frame(&self) -> Option<&Frame> {
+        self.frames.get(self.current)
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+}
@@ -0,0 +1,449 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::world::GameEvent;
+
+pub const SAMPLE_RATE: u32 = 44_100;
+// Most sound effects playing at once, the oldest one is cut off beyond that.
+const MAX_VOICES: usize = 32;
+// Logical names of the music tracks in the asset manifest.
+pub const MUSIC_GAME: &str = "music_game";
+pub const MUSIC_GAME_OVER: &str = "music_game_over";
+const GAME_OVER_CROSSFADE: f64 = 1.5;
+const WAV_HEADER_LEN: usize = 44;
+
+// Sound effects the game plays. Each is looked up in the asset manifest under
+// its name and generated by the synthesiser when the manifest has none.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Sfx {
+    Shoot,
+    Hit,
+    Explosion,
+    Pickup,
+    Breach,
+    GameOver,
+}
+
+impl Sfx {
+    pub const ALL: [Sfx; 6] = [Sfx::Shoot, Sfx::Hit, Sfx::Explosion, Sfx::Pickup, Sfx::Breach, Sfx::GameOver];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Sfx::Shoot => "shoot",
+            Sfx::Hit => "hit",
+            Sfx::Explosion => "explosion",
+            Sfx::Pickup => "pickup",
+            Sfx::Breach => "breach",
+            Sfx::GameOver => "game_over",
+        }
+    }
+}
+
+// Mono samples between -1 and 1, shared by every voice playing them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sound {
+    pub rate: u32,
+    pub samples: Arc<[f32]>,
+}
+
+impl Sound {
+    // Decode an uncompressed 8 or 16 bit PCM WAV file. Stereo is mixed down.
+    pub fn from_wav(bytes: &[u8]) -> Result<Sound, String> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err("not a WAV file".to_string());
+        }
+
+        let mut format = None;
+        let mut pos = 12;
+        while pos + 8 <= bytes.len() {
+            let id = &bytes[pos..pos + 4];
+            let len = u32::from_le_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]]) as usize;
+            let body = &bytes[pos + 8..(pos + 8 + len).min(bytes.len())];
+            match id {
+                b"fmt " if body.len() >= 16 => {
+                    let tag = u16::from_le_bytes([body[0], body[1]]);
+                    let channels = u16::from_le_bytes([body[2], body[3]]);
+                    let rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                    let bits = u16::from_le_bytes([body[14], body[15]]);
+                    if tag != 1 || channels == 0 || !(bits == 8 || bits == 16) {
+                        return Err(format!("unsupported WAV format ({} channels, {} bits, format {})", channels, bits, tag));
+                    }
+                    format = Some((channels as usize, rate, bits));
+                },
+                b"data" => {
+                    let (channels, rate, bits) = format.ok_or("WAV data before format")?;
+                    let frames: Vec<f32> = if bits == 8 {
+                        body.iter().map(|b| (f32::from(*b) - 128.0) / 128.0).collect()
+                    } else {
+                        body.chunks_exact(2).map(|b| f32::from(i16::from_le_bytes([b[0], b[1]])) / 32768.0).collect()
+                    };
+                    let samples = frames.chunks_exact(channels)
+                        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                        .collect();
+                    return Ok(Sound { rate, samples });
+                },
+                _ => (),
+            }
+            // Chunks are padded to an even length.
+            pos += 8 + len + (len & 1);
+        }
+        Err("WAV file without data".to_string())
+    }
+
+    // 16 bit mono PCM WAV file.
+    pub fn to_wav(&self) -> Vec<u8> {
+        wav_bytes(self.rate, &self.samples)
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / f64::from(self.rate.max(1))
+    }
+
+    // Sample at a fractional position, linearly interpolated.
+    fn sample_at(&self, pos: f64) -> f32 {
+        let i = pos as usize;
+        let a = self.samples.get(i).copied().unwrap_or(0.0);
+        let b = self.samples.get(i + 1).copied().unwrap_or(0.0);
+        a + (b - a) * (pos - i as f64) as f32
+    }
+}
+
+fn wav_bytes(rate: u32, samples: &[f32]) -> Vec<u8> {
+    let data_len = samples.len() as u32 * 2;
+    let mut out = wav_header(rate, data_len);
+    out.extend(pcm_bytes(samples));
+    out
+}
+
+// Header of a 16 bit mono PCM WAV file with `data_len` bytes of samples.
+fn wav_header(rate: u32, data_len: u32) -> Vec<u8> {
+    let mut out = Vec::with_capacity(WAV_HEADER_LEN + data_len as usize);
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&(36 + data_len).to_le_bytes());
+    out.extend_from_slice(b"WAVEfmt ");
+    out.extend_from_slice(&16u32.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&rate.to_le_bytes());
+    out.extend_from_slice(&(rate * 2).to_le_bytes());
+    out.extend_from_slice(&2u16.to_le_bytes());
+    out.extend_from_slice(&16u16.to_le_bytes());
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&data_len.to_le_bytes());
+    out
+}
+
+fn pcm_bytes(samples: &[f32]) -> Vec<u8> {
+    samples.iter().flat_map(|sample| ((sample.clamp(-1.0, 1.0) * 32767.0) as i16).to_le_bytes()).collect()
+}
+
+// Where mixed audio goes. Implementations don't need a sound device, which
+// keeps tests and headless runs quiet.
+pub trait AudioBackend {
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+    fn submit(&mut self, samples: &[f32]) -> Result<(), String>;
+    // Called once when the game shuts down.
+    fn finish(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// Throws everything away, but counts it.
+#[derive(Debug, Default)]
+pub struct NullBackend {
+    pub samples: u64,
+}
+
+impl AudioBackend for NullBackend {
+    fn submit(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.samples += samples.len() as u64;
+        Ok(())
+    }
+}
+
+// Records everything into a WAV file as it is mixed. The sizes in the header
+// are filled in when the game ends, WAV files stop at 4 GiB of samples, a bit
+// over 13 hours.
+pub struct WavFileBackend {
+    path: PathBuf,
+    file: Option<BufWriter<File>>,
+    data_len: u32,
+}
+
+impl WavFileBackend {
+    pub fn create(path: PathBuf) -> Result<WavFileBackend, String> {
+        let mut file = File::create(&path)
+            .map(BufWriter::new)
+            .map_err(|err| format!("Unable to create '{}': {}", path.display(), err))?;
+        file.write_all(&wav_header(SAMPLE_RATE, 0))
+            .map_err(|err| format!("Unable to write '{}': {}", path.display(), err))?;
+        Ok(WavFileBackend { path, file: Some(file), data_len: 0 })
+    }
+
+    fn error(&self, err: std::io::Error) -> String {
+        format!("Unable to write '{}': {}", self.path.display(), err)
+    }
+}
+
+impl AudioBackend for WavFileBackend {
+    fn submit(&mut self, samples: &[f32]) -> Result<(), String> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        let bytes = pcm_bytes(samples);
+        let data_len = u32::try_from(bytes.len()).ok()
+            .and_then(|len| self.data_len.checked_add(len))
+            .filter(|len| len.checked_add(WAV_HEADER_LEN as u32).is_some())
+            .ok_or_else(|| format!("'{}' is full, WAV files stop at 4 GiB", self.path.display()))?;
+        file.write_all(&bytes).map_err(|err| self.error(err))?;
+        self.data_len = data_len;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        let Some(mut file) = self.file.take() else {
+            return Ok(());
+        };
+        let header = wav_header(SAMPLE_RATE, self.data_len);
+        file.seek(SeekFrom::Start(0))
+            .and_then(|_| file.write_all(&header))
+            .and_then(|_| file.flush())
+            .map_err(|err| self.error(err))
+    }
+}
+
+impl Drop for WavFileBackend {
+    fn drop(&mut self) {
+        if let Err(msg) = self.finish() {
+            eprintln!("{}", msg);
+        }
+    }
+}
+
+// Streams raw 32 bit float mono samples into an external player's stdin,
+// e.g. `aplay -q -f FLOAT_LE -r 44100 -c 1` or
+// `ffplay -nodisp -f f32le -ar 44100 -ac 1 -`.
+pub struct PipeBackend {
+    child: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl PipeBackend {
+    pub fn spawn(command: &str) -> Result<PipeBackend, String> {
+        let mut parts = command.split_whitespace();
+        let program = parts.next().ok_or("Empty audio command")?;
+        let mut child = Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("Unable to start audio command '{}': {}", command, err))?;
+        let stdin = child.stdin.take();
+        Ok(PipeBackend { child, stdin })
+    }
+}
+
+impl AudioBackend for PipeBackend {
+    fn submit(&mut self, samples: &[f32]) -> Result<(), String> {
+        let Some(stdin) = self.stdin.as_mut() else {
+            return Ok(());
+        };
+        let bytes: Vec<u8> = samples.iter().flat_map(|sample| sample.to_le_bytes()).collect();
+        stdin.write_all(&bytes).map_err(|err| format!("Audio command stopped: {}", err))
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.stdin = None;
+        self.child.wait().map(|_| ()).map_err(|err| err.to_string())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Volumes {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for Volumes {
+    fn default() -> Volumes {
+        Volumes { master: 1.0, music: 0.6, sfx: 0.8 }
+    }
+}
+
+struct Voice {
+    sound: Sound,
+    // Position in the sound's own samples.
+    pos: f64,
+}
+
+struct Track {
+    name: String,
+    sound: Sound,
+    pos: f64,
+    gain: f32,
+    // Gain change per output sample, the track is dropped once it fades out.
+    fade: f32,
+}
+
+// Plays sound effects and music through a backend. Mixing is driven by the
+// caller handing over elapsed time, so it follows the game loop.
+pub struct Audio {
+    backend: Box<dyn AudioBackend>,
+    pub volumes: Volumes,
+    sounds: HashMap<String, Sound>,
+    voices: Vec<Voice>,
+    tracks: Vec<Track>,
+    // Fraction of a sample owed from the last call to `advance`.
+    owed: f64,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn AudioBackend>, volumes: Volumes) -> Audio {
+        Audio {
+            backend,
+            volumes,
+            sounds: HashMap::new(),
+            voices: Vec::new(),
+            tracks: Vec::new(),
+            owed: 0.0,
+        }
+    }
+
+    pub fn silent() -> Audio {
+        Audio::new(Box::new(NullBackend::default()), Volumes::default())
+    }
+
+    // Add or replace a sound, effects and music alike.
+    pub fn set_sound(&mut self, name: &str, sound: Sound) {
+        self.sounds.insert(name.to_string(), sound);
+    }
+
+    pub fn has_sound(&self, name: &str) -> bool {
+        self.sounds.contains_key(name)
+    }
+
+    pub fn play(&mut self, sfx: Sfx) {
+        let Some(sound) = self.sounds.get(sfx.name()) else {
+            return;
+        };
+        if self.voices.len() >= MAX_VOICES {
+            self.voices.remove(0);
+        }
+        // Cloning a sound only shares its samples.
+        self.voices.push(Voice { sound: sound.clone(), pos: 0.0 });
+    }
+
+    pub fn playing(&self) -> usize {
+        self.voices.len()
+    }
+
+    // Switch to another looping track, fading the current one out over
+    // `crossfade` seconds while the new one fades in.
+    pub fn play_music(&mut self, name: &str, crossfade: f64) {
+        if self.music() == Some(name) {
+            return;
+        }
+        let step = self.fade_step(crossfade);
+        for track in self.tracks.iter_mut() {
+            track.fade = -step;
+        }
+        if let Some(sound) = self.sounds.get(name) {
+            self.tracks.push(Track { name: name.to_string(), sound: sound.clone(), pos: 0.0, gain: 0.0, fade: step });
+        }
+    }
+
+    pub fn stop_music(&mut self, fade_out: f64) {
+        let step = self.fade_step(fade_out);
+        for track in self.tracks.iter_mut() {
+            track.fade = -step;
+        }
+    }
+
+    // Track that is playing or fading in.
+    pub fn music(&self) -> Option<&str> {
+        self.tracks.iter().rev().find(|track| track.fade >= 0.0).map(|track| track.name.as_str())
+    }
+
+    fn fade_step(&self, seconds: f64) -> f32 {
+        (1.0 / (seconds * f64::from(self.backend.sample_rate())).max(1.0)) as f32
+    }
+
+    // Play whatever the game's events call for.
+    pub fn handle(&mut self, events: &[GameEvent]) {
+        for event in events {
+            match event {
+                GameEvent::Shot => self.play(Sfx::Shoot),
+                GameEvent::Kill { .. } => {
+                    self.play(Sfx::Hit);
+                    self.play(Sfx::Explosion);
+                },
+                GameEvent::Pickup { .. } => self.play(Sfx::Pickup),
+                GameEvent::Breach { .. } => self.play(Sfx::Breach),
+                GameEvent::GameOver { .. } => {
+                    self.play(Sfx::GameOver);
+                    self.play_music(MUSIC_GAME_OVER, GAME_OVER_CROSSFADE);
+                },
+            }
+        }
+    }
+
+    // Mix `dt` seconds of audio and hand it to the backend.
+    pub fn advance(&mut self, dt: f64) -> Result<(), String> {
+        let rate = self.backend.sample_rate();
+        self.owed += dt * f64::from(rate);
+        let count = self.owed.floor() as usize;
+        self.owed -= count as f64;
+        if count == 0 {
+            return Ok(());
+        }
+
+        let mut out = vec![0.0f32; count];
+        let sfx_gain = self.volumes.master * self.volumes.sfx;
+        for voice in self.voices.iter_mut() {
+            let step = f64::from(voice.sound.rate) / f64::from(rate);
+            for sample in out.iter_mut() {
+                if voice.pos >= voice.sound.samples.len() as f64 {
+                    break;
+                }
+                *sample += voice.sound.sample_at(voice.pos) * sfx_gain;
+                voice.pos += step;
+            }
+        }
+        self.voices.retain(|voice| voice.pos < voice.sound.samples.len() as f64);
+
+        let music_gain = self.volumes.master * self.volumes.music;
+        for track in self.tracks.iter_mut() {
+            let len = track.sound.samples.len() as f64;
+            if len == 0.0 {
+                continue;
+            }
+            let step = f64::from(track.sound.rate) / f64::from(rate);
+            for sample in out.iter_mut() {
+                track.gain = (track.gain + track.fade).clamp(0.0, 1.0);
+                *sample += track.sound.sample_at(track.pos) * track.gain * music_gain;
+                track.pos = (track.pos + step) % len;
+            }
+        }
+        self.tracks.retain(|track| track.fade >= 0.0 || track.gain > 0.0);
+
+        for sample in out.iter_mut() {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+        self.backend.submit(&out)
+    }
+
+    pub fn finish(&mut self) -> Result<(), String> {
+        self.backend.finish()
+    }
+}
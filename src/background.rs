@@ -0,0 +1,104 @@
+use piston::window::Size;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+
+// Scroll speeds are in pixels per update at pace 1.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LayerDef {
+    Stars { count: usize, speed: f64, size: f64, color: [f32; 4] },
+    // Large faint blobs of colour.
+    Nebula { count: usize, speed: f64, size: f64, color: [f32; 4] },
+    // A texture from the asset manifest, tiled vertically.
+    Texture {
+        texture: String,
+        speed: f64,
+        #[serde(default = "opaque")]
+        tint: [f32; 4],
+    },
+}
+
+fn opaque() -> [f32; 4] {
+    [1.0; 4]
+}
+
+pub struct Layer {
+    pub def: LayerDef,
+    // How far the layer has scrolled, in pixels.
+    pub offset: f64,
+    // Stars and blobs as [x, y, size], x and y relative to the playfield so
+    // they survive resizes.
+    points: Vec<[f64; 3]>,
+}
+
+// Layers drawn behind everything else, furthest first. Scrolling speed follows
+// the game's pace, so the field rushes by faster as things get hectic.
+pub struct Background {
+    pub layers: Vec<Layer>,
+    pub pace: f64,
+}
+
+impl Default for Background {
+    fn default() -> Background {
+        Background::new(&Background::default_layers(), 0)
+    }
+}
+
+impl Background {
+    pub fn new(defs: &[LayerDef], seed: u64) -> Background {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let layers = defs.iter().map(|def| {
+            let points = match def {
+                LayerDef::Stars { count, size, .. } | LayerDef::Nebula { count, size, .. } => (0..*count)
+                    .map(|_| [rng.gen::<f64>(), rng.gen::<f64>(), size * rng.gen_range(0.5..=1.0)])
+                    .collect(),
+                LayerDef::Texture { .. } => Vec::new(),
+            };
+            Layer { def: def.clone(), offset: 0.0, points }
+        }).collect();
+
+        Background { layers, pace: 1.0 }
+    }
+
+    // Three star layers and a nebula, the default look.
+    pub fn default_layers() -> Vec<LayerDef> {
+        vec![
+            LayerDef::Nebula { count: 4, speed: 0.05, size: 220.0, color: [0.25, 0.1, 0.4, 0.12] },
+            LayerDef::Stars { count: 80, speed: 0.15, size: 1.0, color: [0.5, 0.5, 0.6, 1.0] },
+            LayerDef::Stars { count: 40, speed: 0.4, size: 1.5, color: [0.75, 0.75, 0.85, 1.0] },
+            LayerDef::Stars { count: 15, speed: 0.9, size: 2.0, color: [1.0, 1.0, 1.0, 1.0] },
+        ]
+    }
+
+    pub fn defs(&self) -> Vec<LayerDef> {
+        self.layers.iter().map(|layer| layer.def.clone()).collect()
+    }
+
+    pub fn update(&mut self, pace: f64) {
+        self.pace = pace;
+        for layer in self.layers.iter_mut() {
+            let speed = match layer.def {
+                LayerDef::Stars { speed, .. } | LayerDef::Nebula { speed, .. } | LayerDef::Texture { speed, .. } => speed,
+            };
+            layer.offset += speed * pace;
+        }
+    }
+}
+
+impl Layer {
+    // Stars or blobs of this layer on a playfield of the given size, as [x, y, size].
+    pub fn points(&self, size: Size) -> impl Iterator<Item = [f64; 3]> + '_ {
+        let margin = match self.def {
+            LayerDef::Nebula { size, .. } => size,
+            _ => 0.0,
+        };
+        // Wrap around a band slightly taller than the screen, so big blobs
+        // leave at the bottom before coming back at the top.
+        let height = size.height + 2.0 * margin;
+        self.points.iter().map(move |point| {
+            let y = (point[1] * height + self.offset).rem_euclid(height) - margin;
+            [point[0] * size.width, y, point[2]]
+        })
+    }
+}
@@ -0,0 +1,282 @@
+use std::fmt::Write;
+use std::thread;
+
+use piston::input::{Button, Key};
+use piston::window::Size;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
+
+use crate::bot::Bot;
+use crate::world::{GameStatus, Rules, World};
+
+const DT: f64 = 1.0 / 120.0;
+// Width of the buckets breaches are grouped into, in updates.
+pub const BREACH_BUCKET: u64 = 1000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Policy {
+    // The autopilot from `bot`.
+    Bot,
+    // Never touches the controls.
+    Idle,
+    // Mashes random keys, changing its mind every few updates.
+    Random,
+}
+
+impl Policy {
+    pub fn parse(name: &str) -> Result<Policy, String> {
+        match name {
+            "bot" => Ok(Policy::Bot),
+            "idle" => Ok(Policy::Idle),
+            "random" => Ok(Policy::Random),
+            _ => Err(format!("Unknown policy '{}', expected bot, idle or random", name)),
+        }
+    }
+}
+
+enum Controller {
+    Bot(Bot),
+    Idle,
+    Random { rng: Box<ChaCha8Rng>, held: Option<Key> },
+}
+
+impl Controller {
+    fn new(policy: Policy, seed: u64) -> Controller {
+        match policy {
+            Policy::Bot => Controller::Bot(Bot::new()),
+            Policy::Idle => Controller::Idle,
+            // Offset the seed so the policy does not mirror the spawn rolls.
+            Policy::Random => Controller::Random { rng: Box::new(ChaCha8Rng::seed_from_u64(!seed)), held: None },
+        }
+    }
+
+    fn act(&mut self, world: &World) -> Vec<(Button, bool)> {
+        match self {
+            Controller::Bot(bot) => bot.act(world),
+            Controller::Idle => Vec::new(),
+            Controller::Random { rng, held } => {
+                let mut events = Vec::new();
+                if !world.uptime.is_multiple_of(30) {
+                    return events;
+                }
+                if let Some(key) = held.take() {
+                    events.push((Button::Keyboard(key), false));
+                }
+                let keys = [Key::Left, Key::Right, Key::Up, Key::Down];
+                let key = keys[rng.gen_range(0..keys.len())];
+                events.push((Button::Keyboard(key), true));
+                *held = Some(key);
+                events.push((Button::Keyboard(Key::Space), rng.gen_bool(0.5)));
+                events
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Win,
+    Died,
+    // Still alive when `max_ticks` ran out.
+    Timeout,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GameResult {
+    pub seed: u64,
+    pub score: u32,
+    pub survival_ticks: u64,
+    pub outcome: Outcome,
+    // Update on which every breach happened.
+    pub breach_ticks: Vec<u64>,
+}
+
+// Play a single seeded game to the end.
+pub fn play(rules: Rules, policy: Policy, seed: u64, max_ticks: u64) -> GameResult {
+    let mut world = World::with_seed(Size { width: 400.0, height: 600.0 }, seed);
+    world.rules = rules;
+    world.reset_with_seed(seed);
+
+    let mut controller = Controller::new(policy, seed);
+    let mut breach_ticks = Vec::new();
+
+    while world.game_status == GameStatus::Normal && world.uptime < max_ticks {
+        for (button, press) in controller.act(&world) {
+            world.input(&button, press);
+        }
+        let breaches = world.breaches;
+        world.update(DT);
+        for _ in breaches..world.breaches {
+            breach_ticks.push(world.uptime);
+        }
+    }
+
+    GameResult {
+        seed,
+        score: world.score,
+        survival_ticks: world.uptime,
+        outcome: match world.game_status {
+            GameStatus::Win => Outcome::Win,
+            GameStatus::Died => Outcome::Died,
+            GameStatus::Normal => Outcome::Timeout,
+        },
+        breach_ticks,
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Distribution {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub p10: f64,
+    pub median: f64,
+    pub p90: f64,
+}
+
+impl Distribution {
+    pub fn new(mut values: Vec<f64>) -> Distribution {
+        if values.is_empty() {
+            values.push(0.0);
+        }
+        values.sort_by(f64::total_cmp);
+        let at = |q: f64| values[((values.len() - 1) as f64 * q).round() as usize];
+
+        Distribution {
+            min: values[0],
+            max: values[values.len() - 1],
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            p10: at(0.1),
+            median: at(0.5),
+            p90: at(0.9),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Summary {
+    pub rules: Rules,
+    pub policy: Policy,
+    pub games: usize,
+    pub win_rate: f64,
+    pub score: Distribution,
+    pub survival_ticks: Distribution,
+    // Update of the first breach, over the games that had one.
+    pub first_breach: Distribution,
+    // Number of breaches per `BREACH_BUCKET` updates, over all games.
+    pub breaches_per_bucket: Vec<u32>,
+}
+
+impl Summary {
+    pub fn new(rules: Rules, policy: Policy, results: &[GameResult]) -> Summary {
+        let wins = results.iter().filter(|r| r.outcome == Outcome::Win).count();
+
+        let mut breaches_per_bucket: Vec<u32> = Vec::new();
+        for tick in results.iter().flat_map(|r| r.breach_ticks.iter()) {
+            let bucket = (tick / BREACH_BUCKET) as usize;
+            if breaches_per_bucket.len() <= bucket {
+                breaches_per_bucket.resize(bucket + 1, 0);
+            }
+            breaches_per_bucket[bucket] += 1;
+        }
+
+        Summary {
+            rules,
+            policy,
+            games: results.len(),
+            win_rate: if results.is_empty() { 0.0 } else { wins as f64 / results.len() as f64 },
+            score: Distribution::new(results.iter().map(|r| f64::from(r.score)).collect()),
+            survival_ticks: Distribution::new(results.iter().map(|r| r.survival_ticks as f64).collect()),
+            first_breach: Distribution::new(results.iter()
+                .filter_map(|r| r.breach_ticks.first().map(|t| *t as f64))
+                .collect()),
+            breaches_per_bucket,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchRun {
+    pub summary: Summary,
+    pub games: Vec<GameResult>,
+}
+
+#[derive(Clone, Debug)]
+pub struct BatchConfig {
+    pub games: u64,
+    pub first_seed: u64,
+    pub threads: usize,
+    pub policy: Policy,
+    pub max_ticks: u64,
+}
+
+// Play `config.games` games with consecutive seeds, spread over
+// `config.threads` threads. Results are ordered by seed regardless of the
+// thread count.
+pub fn run(rules: Rules, config: &BatchConfig) -> BatchRun {
+    let threads = config.threads.max(1) as u64;
+    let mut games: Vec<GameResult> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads).map(|t| {
+            scope.spawn(move || {
+                (0..config.games)
+                    .filter(|i| i % threads == t)
+                    .map(|i| play(rules, config.policy, config.first_seed + i, config.max_ticks))
+                    .collect::<Vec<_>>()
+            })
+        }).collect();
+
+        handles.into_iter()
+            .flat_map(|handle| handle.join().expect("Simulation thread panicked"))
+            .collect()
+    });
+    games.sort_by_key(|game| game.seed);
+
+    BatchRun {
+        summary: Summary::new(rules, config.policy, &games),
+        games,
+    }
+}
+
+// Expand `name=v1,v2,...` sweeps into every combination applied on top of
+// `base`, each checked once all its values are set.
+pub fn sweep(base: Rules, sweeps: &[(String, Vec<String>)]) -> Result<Vec<Rules>, String> {
+    let mut combinations = vec![base];
+    for (name, values) in sweeps {
+        let mut next = Vec::with_capacity(combinations.len() * values.len());
+        for rules in &combinations {
+            for value in values {
+                let mut rules = *rules;
+                rules.set(name, value)?;
+                next.push(rules);
+            }
+        }
+        combinations = next;
+    }
+    for rules in &combinations {
+        rules.validate()?;
+    }
+    Ok(combinations)
+}
+
+// One row per game, prefixed with the rules it was played with.
+pub fn to_csv(runs: &[BatchRun]) -> String {
+    let mut out = String::new();
+    out.push_str(&Rules::NAMES.join(","));
+    out.push_str(",policy,seed,score,survival_ticks,outcome,breaches,first_breach\n");
+
+    for run in runs {
+        let r = &run.summary.rules;
+        for game in &run.games {
+            let _ = writeln!(out, "{},{},{},{},{},{},{},{:?},{},{},{},{:?},{},{}",
+                r.shoot_cooldown, r.enemy_spawn_cooldown, r.min_spawn_interval, r.difficulty_interval,
+                r.spawn_chance, r.start_ammo, r.ammo_per_kill,
+                run.summary.policy, game.seed, game.score, game.survival_ticks, game.outcome,
+                game.breach_ticks.len(),
+                game.breach_ticks.first().map(|t| t.to_string()).unwrap_or_default());
+        }
+    }
+    out
+}
@@ -0,0 +1,124 @@
+use std::{env, fs, process, thread};
+
+use basic_game::batch::{self, BatchConfig, Policy};
+use basic_game::world::Rules;
+
+const USAGE: &str = "\
+Usage: batch [OPTIONS]
+
+Plays seeded games without a window and reports how they went.
+
+Options:
+    --games <N>             games per rule set (default 1000)
+    --seed <N>              seed of the first game (default 0)
+    --threads <N>           worker threads (default: all cores)
+    --policy <NAME>         bot, idle or random (default bot)
+    --max-ticks <N>         give up on a game after this many updates (default 30000)
+    --set <RULE=VALUE>      override a rule, may be repeated
+    --sweep <RULE=V1,V2..>  try every value of a rule, may be repeated
+    --format <FORMAT>       summary, csv or json (default summary)
+    --output <PATH>         write to a file instead of stdout
+    -h, --help              print this message";
+
+enum Format {
+    Summary,
+    Csv,
+    Json,
+}
+
+struct Args {
+    config: BatchConfig,
+    rules: Rules,
+    sweeps: Vec<(String, Vec<String>)>,
+    format: Format,
+    output: Option<String>,
+}
+
+fn split_rule(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, value)) => Ok((name.to_string(), value.to_string())),
+        None => Err(format!("Expected RULE=VALUE, got '{}'", arg)),
+    }
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut config = BatchConfig {
+        games: 1000,
+        first_seed: 0,
+        threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        policy: Policy::Bot,
+        max_ticks: 30_000,
+    };
+    let mut rules = Rules::default();
+    let mut sweeps = Vec::new();
+    let mut format = Format::Summary;
+    let mut output = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("Missing value for '{}'", arg));
+        match arg.as_str() {
+            "--games" => config.games = value()?.parse().map_err(|_| "Invalid --games")?,
+            "--seed" => config.first_seed = value()?.parse().map_err(|_| "Invalid --seed")?,
+            "--threads" => config.threads = value()?.parse().map_err(|_| "Invalid --threads")?,
+            "--max-ticks" => config.max_ticks = value()?.parse().map_err(|_| "Invalid --max-ticks")?,
+            "--policy" => config.policy = Policy::parse(&value()?)?,
+            "--set" => {
+                let (name, value) = split_rule(&value()?)?;
+                rules.set(&name, &value)?;
+            },
+            "--sweep" => {
+                let (name, values) = split_rule(&value()?)?;
+                sweeps.push((name, values.split(',').map(str::to_string).collect()));
+            },
+            "--format" => format = match value()?.as_str() {
+                "summary" => Format::Summary,
+                "csv" => Format::Csv,
+                "json" => Format::Json,
+                other => return Err(format!("Unknown format '{}'", other)),
+            },
+            "--output" => output = Some(value()?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => return Err(format!("Unknown option '{}'\n\n{}", arg, USAGE)),
+        }
+    }
+
+    Ok(Args { config, rules, sweeps, format, output })
+}
+
+fn main() {
+    let Args { config, rules, sweeps, format, output } = parse_args().unwrap_or_else(|msg| {
+        eprintln!("{}", msg);
+        process::exit(2);
+    });
+
+    let rule_sets = batch::sweep(rules, &sweeps).unwrap_or_else(|msg| {
+        eprintln!("{}", msg);
+        process::exit(2);
+    });
+
+    let runs: Vec<_> = rule_sets.into_iter().map(|rules| {
+        let run = batch::run(rules, &config);
+        eprintln!("{:?}: win rate {:.3}, mean score {:.1}", rules, run.summary.win_rate, run.summary.score.mean);
+        run
+    }).collect();
+
+    let text = match format {
+        Format::Csv => batch::to_csv(&runs),
+        Format::Json => serde_json::to_string_pretty(&runs).expect("Unable to serialise results"),
+        Format::Summary => {
+            let summaries: Vec<_> = runs.iter().map(|run| &run.summary).collect();
+            serde_json::to_string_pretty(&summaries).expect("Unable to serialise results")
+        }
+    };
+
+    match output {
+        Some(path) => {
+            if let Err(err) = fs::write(&path, text) {
+                eprintln!("Unable to write {}: {}", path, err);
+                process::exit(1);
+            }
+        },
+        None => println!("{}", text),
+    }
+}
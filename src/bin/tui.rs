@@ -0,0 +1,143 @@
+extern crate piston;
+extern crate termion;
+
+use std::io::{stdout, Read, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use basic_game::tui::Screen;
+use basic_game::world::World;
+use piston::input::{Button, Key, MouseButton};
+use piston::window::Size;
+use termion::raw::IntoRawMode;
+
+const UPDATES_PER_SECOND: u64 = 120;
+const FRAMES_PER_SECOND: u64 = 30;
+
+// Terminals only report key presses (plus auto repeat), never releases. A held
+// key is considered released once no repeat arrived within these delays.
+const FIRST_REPEAT_DELAY: Duration = Duration::from_millis(550);
+const REPEAT_DELAY: Duration = Duration::from_millis(150);
+
+struct HeldKey {
+    key: Key,
+    last_seen: Instant,
+    repeated: bool,
+}
+
+fn map_key(byte: u8, escape: &mut Vec<u8>) -> Option<Key> {
+    // Arrow keys arrive as `ESC [ A` .. `ESC [ D`.
+    if !escape.is_empty() || byte == 0x1b {
+        escape.push(byte);
+        if escape.len() < 3 {
+            return None;
+        }
+        let key = match escape[..] {
+            [0x1b, b'[', b'A'] => Some(Key::Up),
+            [0x1b, b'[', b'B'] => Some(Key::Down),
+            [0x1b, b'[', b'C'] => Some(Key::Right),
+            [0x1b, b'[', b'D'] => Some(Key::Left),
+            _ => None,
+        };
+        escape.clear();
+        return key;
+    }
+
+    match byte {
+        b' ' => Some(Key::Space),
+        b'\r' | b'\n' => Some(Key::Return),
+        b'q' | 0x03 => Some(Key::Q),
+        b'w' => Some(Key::Up),
+        b's' => Some(Key::Down),
+        b'd' => Some(Key::Right),
+        b'a' => Some(Key::Left),
+        _ => None,
+    }
+}
+
+fn main() {
+    let (cols, rows) = termion::terminal_size().unwrap_or((80, 24));
+    let mut screen = Screen::new(cols as usize, rows as usize);
+    let mut world = World::new(Size { width: 400.0, height: 600.0 });
+    world.highscore = basic_game::load_highscore();
+
+    let mut out = stdout().into_raw_mode().expect("Unable to switch the terminal to raw mode");
+    write!(out, "{}{}", termion::clear::All, termion::cursor::Hide).unwrap();
+
+    let mut input = termion::async_stdin();
+    let mut escape: Vec<u8> = Vec::new();
+    let mut held: Option<HeldKey> = None;
+
+    let update_interval = Duration::from_micros(1_000_000 / UPDATES_PER_SECOND);
+    let frame_interval = Duration::from_micros(1_000_000 / FRAMES_PER_SECOND);
+    let dt = 1.0 / UPDATES_PER_SECOND as f64;
+    let mut next_update = Instant::now();
+    let mut next_frame = Instant::now();
+
+    'game: loop {
+        let mut bytes = [0u8; 64];
+        let count = input.read(&mut bytes).unwrap_or(0);
+        for byte in &bytes[..count] {
+            let key = match map_key(*byte, &mut escape) {
+                Some(key) => key,
+                None => continue,
+            };
+            match key {
+                Key::Q => break 'game,
+                Key::Return => world.input(&Button::Mouse(MouseButton::Left), false),
+                Key::Space => world.input(&Button::Keyboard(key), true),
+                _ => {
+                    let now = Instant::now();
+                    match held {
+                        Some(ref mut held_key) if held_key.key == key => {
+                            held_key.last_seen = now;
+                            held_key.repeated = true;
+                        },
+                        _ => {
+                            if let Some(previous) = held.take() {
+                                world.input(&Button::Keyboard(previous.key), false);
+                            }
+                            world.input(&Button::Keyboard(key), true);
+                            held = Some(HeldKey { key, last_seen: now, repeated: false });
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Some(ref held_key) = held {
+            let delay = if held_key.repeated { REPEAT_DELAY } else { FIRST_REPEAT_DELAY };
+            if held_key.last_seen.elapsed() > delay {
+                world.input(&Button::Keyboard(held_key.key), false);
+                held = None;
+            }
+        }
+
+        let previous_highscore = world.highscore;
+        while Instant::now() >= next_update {
+            world.update(dt);
+            next_update += update_interval;
+        }
+        if world.highscore > previous_highscore {
+            basic_game::save_highscore(world.highscore);
+        }
+
+        if Instant::now() >= next_frame {
+            if let Ok((cols, rows)) = termion::terminal_size() {
+                if cols as usize != screen.cols || rows as usize != screen.rows {
+                    screen = Screen::new(cols as usize, rows as usize);
+                    write!(out, "{}", termion::clear::All).unwrap();
+                }
+            }
+            screen.draw_world(&world);
+            write!(out, "{}", screen.to_ansi()).unwrap();
+            out.flush().unwrap();
+            next_frame += frame_interval;
+        }
+
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    write!(out, "{}{}{}", termion::clear::All, termion::cursor::Goto(1, 1), termion::cursor::Show).unwrap();
+    out.flush().unwrap();
+}
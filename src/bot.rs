@@ -0,0 +1,202 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use piston::input::{Button, Key};
+
+use crate::models::GameObject;
+use crate::spawn::Edge;
+use crate::world::{GameStatus, World};
+
+// Ammo kept back for enemies that are about to breach the defence line.
+const AMMO_RESERVE: u32 = 5;
+// How far below the top of the screen an enemy has to be before the reserve is spent on it.
+const DANGER_ZONE: f64 = 0.5;
+const DT: f64 = 1.0 / 120.0;
+
+// Scripted player that produces the same button events a human would.
+pub struct Bot {
+    held: Option<Key>,
+    fire_held: bool,
+}
+
+impl Default for Bot {
+    fn default() -> Bot {
+        Bot::new()
+    }
+}
+
+impl Bot {
+    pub fn new() -> Bot {
+        Bot { held: None, fire_held: false }
+    }
+
+    // Decide which buttons to press or release this tick. The returned events
+    // are meant to be passed straight to `World::input`/`App::input`.
+    pub fn act(&mut self, world: &World) -> Vec<(Button, bool)> {
+        let mut events = Vec::new();
+
+        if world.game_status != GameStatus::Normal {
+            self.release_all(&mut events);
+            // A mouse release restarts a finished game.
+            events.push((Button::Mouse(piston::input::MouseButton::Left), false));
+            return events;
+        }
+
+        let player = &world.player;
+        let target = self.pick_target(world);
+        let target_x = match target {
+            Some(x) => x,
+            None => world.size.width / 2.0,
+        };
+
+        let mut wanted = None;
+        if let Some(escape) = self.dodge(world) {
+            wanted = Some(escape);
+        } else if (target_x - player.pos.x).abs() > player.radius() / 2.0 {
+            wanted = Some(if target_x < player.pos.x { Key::Left } else { Key::Right });
+        }
+        self.hold(wanted, &mut events);
+
+        // Release the trigger between shots, otherwise no new press event fires.
+        if self.fire_held {
+            events.push((Button::Keyboard(Key::Space), false));
+            self.fire_held = false;
+        } else if target.is_some() && self.should_fire(world, target_x) {
+            events.push((Button::Keyboard(Key::Space), true));
+            self.fire_held = true;
+        }
+
+        events
+    }
+
+    // Column of the enemy closest to breaching, snapped to its spawn lane.
+    fn pick_target(&self, world: &World) -> Option<f64> {
+        let player = &world.player;
+        let lowest = world.enemies.iter()
+            .filter(|enemy| enemy.health > 0.0 && enemy.position().y < player.pos.y)
+            .max_by(|a, b| a.position().y.total_cmp(&b.position().y))?;
+
+        let x = lowest.position().x;
+        let column = world.spawn_points.iter()
+            .filter(|point| point.from == Edge::Top)
+            .map(|point| point.x)
+            .min_by(|a, b| (a - x).abs().total_cmp(&(b - x).abs()))
+            .filter(|column| (column - x).abs() < lowest.radius())
+            .unwrap_or(x);
+        Some(column)
+    }
+
+    // Step sideways when an enemy is about to run into the player.
+    fn dodge(&self, world: &World) -> Option<Key> {
+        let player = &world.player;
+        let threat = world.enemies.iter().find(|enemy| {
+            let pos = enemy.position();
+            let reach = player.radius() + enemy.radius();
+            (pos.x - player.pos.x).abs() < reach
+                && pos.y < player.pos.y
+                && player.pos.y - pos.y < reach
+        })?;
+
+        let escape_left = threat.position().x > player.pos.x;
+        if escape_left && player.pos.x > player.radius() {
+            Some(Key::Left)
+        } else {
+            Some(Key::Right)
+        }
+    }
+
+    fn should_fire(&self, world: &World, target_x: f64) -> bool {
+        let player = &world.player;
+        if world.ammo == 0 || world.shoot_cooldown > 0 {
+            return false;
+        }
+        if (target_x - player.pos.x).abs() > player.radius() / 2.0 {
+            return false;
+        }
+
+        let in_danger = world.enemies.iter().any(|enemy| {
+            enemy.position().y > world.size.height * DANGER_ZONE
+                && (enemy.position().x - target_x).abs() < enemy.radius()
+        });
+        world.ammo > AMMO_RESERVE || in_danger
+    }
+
+    fn hold(&mut self, wanted: Option<Key>, events: &mut Vec<(Button, bool)>) {
+        if self.held == wanted {
+            return;
+        }
+        if let Some(key) = self.held.take() {
+            events.push((Button::Keyboard(key), false));
+        }
+        if let Some(key) = wanted {
+            events.push((Button::Keyboard(key), true));
+            self.held = Some(key);
+        }
+    }
+
+    fn release_all(&mut self, events: &mut Vec<(Button, bool)>) {
+        self.hold(None, events);
+        if self.fire_held {
+            events.push((Button::Keyboard(Key::Space), false));
+            self.fire_held = false;
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SoakReport {
+    pub ticks: u64,
+    pub games: u64,
+    pub wins: u64,
+    pub deaths: u64,
+    pub best_score: u32,
+}
+
+// Let the bot play back to back games for `ticks` updates as fast as possible,
+// checking the world invariants after every update. Panics inside the
+// simulation are caught and reported together with the tick they happened on.
+pub fn soak(world: &mut World, ticks: u64) -> Result<SoakReport, String> {
+    soak_with(world, ticks, |_| ())
+}
+
+// Like `soak`, handing the world to `after_update` after every update.
+pub fn soak_with<F: FnMut(&World)>(world: &mut World, ticks: u64, mut after_update: F) -> Result<SoakReport, String> {
+    let mut bot = Bot::new();
+    let mut report = SoakReport::default();
+    let mut status = world.game_status;
+
+    while report.ticks < ticks {
+        let step = panic::catch_unwind(AssertUnwindSafe(|| {
+            for (button, press) in bot.act(world) {
+                world.input(&button, press);
+            }
+            world.update(DT);
+        }));
+
+        if let Err(cause) = step {
+            let msg = cause.downcast_ref::<&str>().map(|s| s.to_string())
+                .or_else(|| cause.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            return Err(format!("panic in game {} at tick {}: {}", report.games + 1, world.uptime, msg));
+        }
+
+        report.ticks += 1;
+        after_update(world);
+        world.check_invariants()
+            .map_err(|err| format!("game {} tick {}: {}", report.games + 1, world.uptime, err))?;
+
+        if world.game_status != status {
+            match world.game_status {
+                GameStatus::Win => report.wins += 1,
+                GameStatus::Died => report.deaths += 1,
+                GameStatus::Normal => (),
+            }
+            if world.game_status != GameStatus::Normal {
+                report.games += 1;
+                report.best_score = report.best_score.max(world.score);
+            }
+            status = world.game_status;
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,90 @@
+use graphics::{Context, Transformed};
+use piston::window::Size;
+
+// Largest shake offset in pixels and rotation in degrees, reached at full trauma.
+const MAX_SHAKE_OFFSET: f64 = 10.0;
+const MAX_SHAKE_ANGLE: f64 = 2.0;
+// Trauma lost per update.
+const TRAUMA_DECAY: f64 = 0.02;
+
+// View onto the playfield. Shake is driven by "trauma" that events add and
+// that wears off over time; the actual shake grows with its square so small
+// knocks stay subtle. It only moves the picture, never the simulation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    // Playfield point shown in the centre of the screen, relative to the
+    // playfield's own centre.
+    pub offset: [f64; 2],
+    pub zoom: f64,
+    pub trauma: f64,
+    // Accessibility setting, no shake at all when false.
+    pub shake_enabled: bool,
+    // Updates since creation, drives the shake pattern.
+    time: u64,
+}
+
+impl Default for Camera {
+    fn default() -> Camera {
+        Camera::new(true)
+    }
+}
+
+impl Camera {
+    pub fn new(shake_enabled: bool) -> Camera {
+        Camera { offset: [0.0, 0.0], zoom: 1.0, trauma: 0.0, shake_enabled, time: 0 }
+    }
+
+    pub fn add_trauma(&mut self, amount: f64) {
+        if self.shake_enabled {
+            self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+        }
+    }
+
+    pub fn update(&mut self) {
+        self.time += 1;
+        self.trauma = (self.trauma - TRAUMA_DECAY).max(0.0);
+    }
+
+    pub fn reset(&mut self) {
+        self.offset = [0.0, 0.0];
+        self.zoom = 1.0;
+        self.trauma = 0.0;
+    }
+
+    // Current shake as ([x, y] offset, angle). Layered sines instead of random
+    // numbers keep it smooth and the same on every run.
+    pub fn shake(&self) -> ([f64; 2], f64) {
+        if !self.shake_enabled || self.trauma <= 0.0 {
+            return ([0.0, 0.0], 0.0);
+        }
+
+        let amount = self.trauma * self.trauma;
+        let t = self.time as f64;
+        let noise = |a: f64, b: f64| ((t * a).sin() + (t * b + 1.3).sin()) / 2.0;
+        let offset = [
+            MAX_SHAKE_OFFSET * amount * noise(0.9, 2.3),
+            MAX_SHAKE_OFFSET * amount * noise(1.1, 1.7),
+        ];
+        (offset, MAX_SHAKE_ANGLE * amount * noise(0.7, 2.9))
+    }
+
+    // Context that draws the playfield of the given size through the camera.
+    pub fn apply(&self, c: &Context, size: Size) -> Context {
+        let (shake, angle) = self.shake();
+        let center = [size.width / 2.0, size.height / 2.0];
+        c.trans(center[0] + shake[0], center[1] + shake[1])
+            .rot_deg(angle)
+            .zoom(self.zoom)
+            .trans(-center[0] - self.offset[0], -center[1] - self.offset[1])
+    }
+
+    // Playfield position shown at `screen`, a position in playfield
+    // coordinates without the camera. Ignores shake.
+    pub fn to_world(&self, screen: [f64; 2], size: Size) -> [f64; 2] {
+        let center = [size.width / 2.0, size.height / 2.0];
+        [
+            (screen[0] - center[0]) / self.zoom + center[0] + self.offset[0],
+            (screen[1] - center[1]) / self.zoom + center[1] + self.offset[1],
+        ]
+    }
+}
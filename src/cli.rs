@@ -0,0 +1,211 @@
+use std::path::PathBuf;
+
+use crate::audio::Volumes;
+use crate::config::{self, DisplaySettings};
+use crate::spawn::SpawnLayout;
+use crate::snapshot;
+use crate::synth;
+
+// Command line options shared by the game binaries.
+#[derive(Debug)]
+pub struct Options {
+    // Let the scripted bot play instead of reading the keyboard.
+    pub bot: bool,
+    // Run without opening a window, as fast as possible.
+    pub headless: bool,
+    // Number of updates to simulate in headless mode.
+    pub ticks: u64,
+    // Serve the reinforcement learning environment over stdin/stdout.
+    pub gym: bool,
+    // Level files to play in order instead of the endless mode.
+    pub levels: Vec<String>,
+    // Let the difficulty director adapt the endless mode.
+    pub director: bool,
+    // File the director's decisions are appended to.
+    pub director_log: Option<String>,
+    pub spawn_layout: SpawnLayout,
+    pub display: DisplaySettings,
+    // Console commands run at startup.
+    pub script: Option<PathBuf>,
+    // Read console commands from stdin in headless mode instead of letting the bot play.
+    pub console: bool,
+    // Save every input to this replay file when the game closes.
+    pub record: Option<PathBuf>,
+    // Play this replay headless, checking it against its checksums, and exit.
+    pub verify_replay: Option<PathBuf>,
+    // Start from this snapshot or save file instead of a new game.
+    pub load: Option<PathBuf>,
+    // Write a snapshot of the world every N updates.
+    pub snapshot_every: Option<u64>,
+    // Folder snapshots are written to.
+    pub snapshot_dir: PathBuf,
+    // Start with the debug overlay shown, F3 toggles it.
+    pub debug: bool,
+    // Folder fonts and other assets are loaded from.
+    pub assets: Option<PathBuf>,
+    // Record the game's audio into this WAV file.
+    pub audio_out: Option<PathBuf>,
+    // Stream the game's audio into this command's stdin.
+    pub audio_cmd: Option<String>,
+    pub volumes: Volumes,
+    // Don't look for a command line audio player.
+    pub no_audio: bool,
+    // Seed the default sound effects are generated from.
+    pub sound_seed: u64,
+    // Write the generated sound effects into this folder as WAV files and exit.
+    pub export_sounds: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            bot: false,
+            headless: false,
+            ticks: 1_000_000,
+            gym: false,
+            levels: Vec::new(),
+            director: false,
+            director_log: None,
+            spawn_layout: SpawnLayout::default(),
+            display: DisplaySettings::default(),
+            script: None,
+            console: false,
+            record: None,
+            verify_replay: None,
+            load: None,
+            snapshot_every: None,
+            snapshot_dir: PathBuf::from(snapshot::SNAPSHOT_DIR),
+            debug: false,
+            assets: None,
+            audio_out: None,
+            audio_cmd: None,
+            volumes: Volumes::default(),
+            no_audio: false,
+            sound_seed: synth::DEFAULT_SEED,
+            export_sounds: None,
+        }
+    }
+}
+
+pub const USAGE: &str = "\
+Usage: basic_game [OPTIONS]
+
+Options:
+    --bot           let the autopilot play
+    --headless      run the simulation without a window (implies --bot)
+    --ticks <N>     number of updates to run in headless mode
+    --spawn <LAYOUT>
+                    where enemies appear: lanes:N, edges:N or edges:N:top,left,...
+    --level <PATH>  play a level file, repeat to play several in order
+    --director      adapt the difficulty to how well the player does
+    --director-log <PATH>
+                    append the director's decisions to a JSON lines file
+    --fullscreen    open the game fullscreen
+    --no-vsync      do not wait for vertical sync
+    --msaa <N>      multisample anti-aliasing samples (0, 2, 4, 8, 16)
+    --opengl <VER>  require this OpenGL version (default: 3.2, falling back to 2.1)
+    --no-shake      turn off screen shake
+    --script <PATH> run console commands from a file at startup
+    --console       in headless mode, read console commands from stdin
+    --record <PATH> save a replay of the session when the game closes
+    --verify-replay <PATH>
+                    play a replay without a window and report the first update
+                    where the game differs from the recording
+    --load <PATH>   start from a snapshot or save file
+    --snapshot-every <N>
+                    write a snapshot of the world every N updates, F12 writes
+                    one at any time and a panic writes one too
+    --snapshot-dir <DIR>
+                    where snapshots are written (default: data/snapshots)
+    --debug         show the debug overlay (toggle with F3)
+    --assets <DIR>  load assets from this folder (default: $BASIC_GAME_ASSETS
+                    or an assets folder near the working directory)
+    --audio-out <PATH>
+                    record the game's audio into a WAV file
+    --audio-cmd <CMD>
+                    play audio by piping 32 bit float mono samples at 44100 Hz
+                    into CMD, e.g. \"aplay -q -f FLOAT_LE -r 44100 -c 1\"
+    --no-audio      play without sound
+                    The game has no audio device output of its own. Without
+                    --audio-cmd, --audio-out or --no-audio it pipes its sound
+                    into pw-cat, paplay or aplay, whichever starts first, and is
+                    silent when none of them is installed.
+    --master-volume <V>, --music-volume <V>, --sfx-volume <V>
+                    volumes from 0 to 1
+    --sound-seed <N>
+                    generate different default sound effects
+    --export-sounds <DIR>
+                    write the generated sound effects as WAV files and exit
+    --gym           serve the RL environment as JSON lines on stdin/stdout
+    -h, --help      print this message";
+
+impl Options {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--bot" => options.bot = true,
+                "--headless" => {
+                    options.headless = true;
+                    options.bot = true;
+                },
+                "--gym" => options.gym = true,
+                "--fullscreen" => options.display.fullscreen = true,
+                "--no-vsync" => options.display.vsync = false,
+                "--no-shake" => options.display.screen_shake = false,
+                "--msaa" => options.display.samples = parse_value(&arg, args.next())?,
+                "--opengl" => {
+                    let version: String = parse_value(&arg, args.next())?;
+                    options.display.opengl = Some(config::parse_opengl(&version)?);
+                },
+                "--audio-out" => options.audio_out = Some(parse_value(&arg, args.next())?),
+                "--audio-cmd" => options.audio_cmd = Some(parse_value(&arg, args.next())?),
+                "--master-volume" => options.volumes.master = parse_volume(&arg, args.next())?,
+                "--music-volume" => options.volumes.music = parse_volume(&arg, args.next())?,
+                "--sfx-volume" => options.volumes.sfx = parse_volume(&arg, args.next())?,
+                "--no-audio" => options.no_audio = true,
+                "--sound-seed" => options.sound_seed = parse_value(&arg, args.next())?,
+                "--export-sounds" => options.export_sounds = Some(parse_value(&arg, args.next())?),
+                "--debug" => options.debug = true,
+                "--script" => options.script = Some(parse_value(&arg, args.next())?),
+                "--console" => options.console = true,
+                "--record" => options.record = Some(parse_value(&arg, args.next())?),
+                "--verify-replay" => options.verify_replay = Some(parse_value(&arg, args.next())?),
+                "--load" => options.load = Some(parse_value(&arg, args.next())?),
+                "--snapshot-every" => options.snapshot_every = Some(parse_value(&arg, args.next())?),
+                "--snapshot-dir" => options.snapshot_dir = parse_value(&arg, args.next())?,
+                "--assets" => options.assets = Some(parse_value(&arg, args.next())?),
+                "--director" => options.director = true,
+                "--director-log" => {
+                    options.director = true;
+                    options.director_log = Some(parse_value(&arg, args.next())?);
+                },
+                "--spawn" => {
+                    let spec: String = parse_value(&arg, args.next())?;
+                    options.spawn_layout = SpawnLayout::parse(&spec)?;
+                },
+                "--level" => options.levels.push(parse_value(&arg, args.next())?),
+                "--ticks" => options.ticks = parse_value(&arg, args.next())?,
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ => return Err(format!("Unknown option '{}'\n\n{}", arg, USAGE)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("Missing value for '{}'", flag))?;
+    value.parse::<T>().map_err(|_| format!("Invalid value '{}' for '{}'", value, flag))
+}
+
+fn parse_volume(flag: &str, value: Option<String>) -> Result<f32, String> {
+    let volume: f32 = parse_value(flag, value)?;
+    if (0.0..=1.0).contains(&volume) {
+        Ok(volume)
+    } else {
+        Err(format!("Volume for '{}' must be between 0 and 1", flag))
+    }
+}
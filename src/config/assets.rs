@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use opengl_graphics::{GlyphCache, TextureSettings};
+use serde::Deserialize;
+
+use super::sprite::{Atlas, Sprite, SpriteDef};
+use crate::background::LayerDef;
+
+// Environment variable naming the assets folder when none is given on the command line.
+pub const ASSETS_ENV: &str = "BASIC_GAME_ASSETS";
+// Lists every asset by logical name, relative to the assets folder.
+pub const MANIFEST_FILE: &str = "manifest.json";
+// Logical name of the HUD font.
+pub const HUD_FONT: &str = "hud";
+// The same font built into the binary, used whenever the file can't be loaded.
+pub const DEFAULT_FONT: &[u8] = include_bytes!("../assets/fonts/PxPlus_IBM_VGA8.ttf");
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AssetKind {
+    Font,
+    Texture,
+    Sound,
+}
+
+impl AssetKind {
+    fn label(self) -> &'static str {
+        match self {
+            AssetKind::Font => "font",
+            AssetKind::Texture => "texture",
+            AssetKind::Sound => "sound",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssetId {
+    pub kind: AssetKind,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    pub fonts: BTreeMap<String, String>,
+    pub textures: BTreeMap<String, String>,
+    pub sounds: BTreeMap<String, String>,
+    pub atlases: BTreeMap<String, Atlas>,
+    // Art for object types, by the name the object reports.
+    pub sprites: BTreeMap<String, SpriteDef>,
+    // Replaces the default starfield when given.
+    pub background: Option<Vec<LayerDef>>,
+}
+
+struct Asset {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    // File contents, only kept for sounds. Fonts and textures are turned into
+    // GPU resources by the frontend.
+    data: Option<Vec<u8>>,
+}
+
+// Keeps track of the files named in the manifest, caches the ones that don't
+// need a graphics context and notices when any of them changes on disk.
+pub struct AssetManager {
+    root: PathBuf,
+    manifest_modified: Option<SystemTime>,
+    assets: BTreeMap<AssetId, Asset>,
+    sprites: BTreeMap<String, Sprite>,
+    background: Option<Vec<LayerDef>>,
+    // Sprites that couldn't be resolved, reported by `check`.
+    problems: Vec<String>,
+}
+
+impl AssetManager {
+    pub fn open(root: &Path) -> Result<AssetManager, String> {
+        let mut manager = AssetManager {
+            root: root.to_path_buf(),
+            manifest_modified: None,
+            assets: BTreeMap::new(),
+            sprites: BTreeMap::new(),
+            background: None,
+            problems: Vec::new(),
+        };
+        manager.read_manifest()?;
+        Ok(manager)
+    }
+
+    fn read_manifest(&mut self) -> Result<(), String> {
+        let path = self.root.join(MANIFEST_FILE);
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("Unable to read asset manifest '{}': {}", path.display(), err))?;
+        let manifest: Manifest = serde_json::from_str(&text)
+            .map_err(|err| format!("Invalid asset manifest '{}': {}", path.display(), err))?;
+
+        self.manifest_modified = modified(&path);
+        self.assets.clear();
+        let lists = [
+            (AssetKind::Font, &manifest.fonts),
+            (AssetKind::Texture, &manifest.textures),
+            (AssetKind::Sound, &manifest.sounds),
+        ];
+        for (kind, list) in lists {
+            for (name, file) in list {
+                let path = self.root.join(file);
+                let mut asset = Asset { path, modified: None, data: None };
+                load(kind, &mut asset);
+                self.assets.insert(AssetId { kind, name: name.clone() }, asset);
+            }
+        }
+
+        self.sprites.clear();
+        self.problems.clear();
+        for (name, def) in &manifest.sprites {
+            let sprite = def.resolve(&manifest.atlases).and_then(|sprite| {
+                if manifest.textures.contains_key(&sprite.texture) {
+                    Ok(sprite)
+                } else {
+                    Err(format!("unknown texture '{}'", sprite.texture))
+                }
+            });
+            match sprite {
+                Ok(sprite) => {
+                    self.sprites.insert(name.clone(), sprite);
+                },
+                Err(msg) => self.problems.push(format!("sprite '{}': {}", name, msg)),
+            }
+        }
+
+        for layer in manifest.background.iter().flatten() {
+            if let LayerDef::Texture { texture, .. } = layer {
+                if !manifest.textures.contains_key(texture) {
+                    self.problems.push(format!("background layer: unknown texture '{}'", texture));
+                }
+            }
+        }
+        self.background = manifest.background;
+        Ok(())
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn names(&self, kind: AssetKind) -> Vec<&str> {
+        self.assets.keys()
+            .filter(|id| id.kind == kind)
+            .map(|id| id.name.as_str())
+            .collect()
+    }
+
+    // Every asset in the manifest whose file couldn't be read and every sprite
+    // that doesn't make sense, in one message.
+    pub fn check(&self) -> Result<(), String> {
+        let missing = self.assets.iter()
+            .filter(|(_, asset)| asset.modified.is_none())
+            .map(|(id, asset)| format!("{} '{}' ({})", id.kind.label(), id.name, asset.path.display()))
+            .chain(self.problems.iter().cloned())
+            .collect::<Vec<String>>();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Missing assets: {}", missing.join(", ")))
+        }
+    }
+
+    pub fn sprite(&self, name: &str) -> Option<&Sprite> {
+        self.sprites.get(name)
+    }
+
+    pub fn background(&self) -> Option<&[LayerDef]> {
+        self.background.as_deref()
+    }
+
+    // File of an asset, if it is in the manifest and exists.
+    pub fn path(&self, kind: AssetKind, name: &str) -> Option<&Path> {
+        self.assets.get(&AssetId { kind, name: name.to_string() })
+            .filter(|asset| asset.modified.is_some())
+            .map(|asset| asset.path.as_path())
+    }
+
+    pub fn sound(&self, name: &str) -> Option<&[u8]> {
+        self.assets.get(&AssetId { kind: AssetKind::Sound, name: name.to_string() })
+            .and_then(|asset| asset.data.as_deref())
+    }
+
+    // Reload whatever changed on disk since the last call and return what did.
+    // An edited manifest counts as every asset changing.
+    pub fn reload_changed(&mut self) -> Result<Vec<AssetId>, String> {
+        if modified(&self.root.join(MANIFEST_FILE)) != self.manifest_modified {
+            self.read_manifest()?;
+            return Ok(self.assets.keys().cloned().collect());
+        }
+
+        let mut changed = Vec::new();
+        for (id, asset) in self.assets.iter_mut() {
+            if modified(&asset.path) != asset.modified {
+                load(id.kind, asset);
+                changed.push(id.clone());
+            }
+        }
+        Ok(changed)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn load(kind: AssetKind, asset: &mut Asset) {
+    asset.modified = modified(&asset.path);
+    asset.data = match kind {
+        AssetKind::Sound if asset.modified.is_some() => fs::read(&asset.path).ok(),
+        _ => None,
+    };
+}
+
+// Find the assets folder: the given one if any, otherwise an `assets` folder
+// near the working directory.
+pub fn find_assets(explicit: Option<&Path>) -> Result<PathBuf, String> {
+    match explicit {
+        Some(path) if path.is_dir() => Ok(path.to_path_buf()),
+        Some(path) => Err(format!("Assets folder '{}' does not exist", path.display())),
+        None => find_folder::Search::ParentsThenKids(3, 3).for_folder("assets")
+            .map_err(|_| "No assets folder found near the working directory".to_string()),
+    }
+}
+
+// Load a font file, falling back to the built in font.
+pub fn load_font(path: Option<&Path>) -> GlyphCache<'static> {
+    let Some(path) = path else {
+        return builtin_font();
+    };
+
+    match GlyphCache::new(path, (), TextureSettings::new()) {
+        Ok(glyph_cache) => glyph_cache,
+        Err(err) => {
+            eprintln!("Unable to load font '{}': {}, using the built in font", path.display(), err);
+            builtin_font()
+        }
+    }
+}
+
+pub fn builtin_font() -> GlyphCache<'static> {
+    GlyphCache::from_bytes(DEFAULT_FONT, (), TextureSettings::new())
+        .expect("Built in font is invalid")
+}
@@ -1,7 +1,109 @@
 use graphics::{Context, DrawState, Transformed, color::WHITE};
+use graphics::character::CharacterCache;
 use graphics::text::Text;
 use opengl_graphics::{GlGraphics, GlyphCache};
 
+// Distance between two baselines, relative to the font size.
+pub const LINE_SPACING: f64 = 1.25;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+
+// A laid out line, `pos` is where its baseline starts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Line {
+    pub text: String,
+    pub pos: [f64; 2],
+}
+
+// Width of `txt` in pixels, summed from the font's advances. Characters the
+// font can't provide count as half the font size.
+pub fn text_width<C: CharacterCache>(txt: &str, size: u32, gc: &mut C) -> f64 {
+    txt.chars()
+        .map(|ch| gc.character(size, ch)
+            .map(|character| character.advance_width())
+            .unwrap_or(f64::from(size) / 2.0))
+        .sum()
+}
+
+pub fn line_height(size: u32) -> f64 {
+    f64::from(size) * LINE_SPACING
+}
+
+// Break `txt` into lines no wider than `width`. Newlines always start a new
+// line and words that don't fit on a line of their own are split.
+pub fn wrap<C: CharacterCache>(txt: &str, size: u32, width: f64, gc: &mut C) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in txt.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if text_width(&candidate, size, gc) <= width {
+                line = candidate;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            for ch in word.chars() {
+                line.push(ch);
+                if line.chars().count() > 1 && text_width(&line, size, gc) > width {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, ch.to_string()));
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+// Horizontal start of a line of the given width, aligned on `x`.
+fn aligned_x(x: f64, width: f64, align: Align) -> f64 {
+    match align {
+        Align::Left => x,
+        Align::Center => x - width / 2.0,
+        Align::Right => x - width,
+    }
+}
+
+// Lay out the lines of `txt` inside `rect` ([x, y, width, height]), wrapping
+// them to its width.
+pub fn layout<C: CharacterCache>(txt: &str, size: u32, rect: [f64; 4], align: Align, valign: VAlign, gc: &mut C) -> Vec<Line> {
+    let [x, y, width, height] = rect;
+    let lines = wrap(txt, size, width, gc);
+    let block_height = line_height(size) * lines.len() as f64;
+    let top = match valign {
+        VAlign::Top => y,
+        VAlign::Middle => y + (height - block_height) / 2.0,
+        VAlign::Bottom => y + height - block_height,
+    };
+    let anchor = match align {
+        Align::Left => x,
+        Align::Center => x + width / 2.0,
+        Align::Right => x + width,
+    };
+
+    lines.into_iter().enumerate().map(|(i, text)| {
+        let line_x = aligned_x(anchor, text_width(&text, size, gc), align);
+        // The baseline sits one font size below the top of the line.
+        let baseline = top + line_height(size) * i as f64 + f64::from(size);
+        Line { text, pos: [line_x, baseline] }
+    }).collect()
+}
+
 pub fn draw_text(color:[f32; 4], txt: &str, pos: [f64; 2], size: u32, gc: &mut GlyphCache, c: &Context, gl: &mut GlGraphics) {
 
     let transform = c.transform.trans(pos[0], pos[1]);
@@ -11,13 +113,22 @@ pub fn draw_text(color:[f32; 4], txt: &str, pos: [f64; 2], size: u32, gc: &mut G
         .unwrap();
 }
 
-// Draw text centered in the window
-pub fn draw_center(txt: &str, size: u32, bounds: [f64; 2], gc: &mut GlyphCache, c: &Context, gl: &mut GlGraphics) {
-    let half_size = f64::from(size) / 2.0;
-    let num_chars = txt.len() as f64;
+// Draw a single line with its baseline on `pos`, aligned horizontally on it.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_aligned(color: [f32; 4], txt: &str, pos: [f64; 2], align: Align, size: u32, gc: &mut GlyphCache, c: &Context, gl: &mut GlGraphics) {
+    let x = aligned_x(pos[0], text_width(txt, size, gc), align);
+    draw_text(color, txt, [x, pos[1]], size, gc, c, gl);
+}
 
-    let x = (bounds[0] / 2.0) - (num_chars * half_size) / 2.0;
-    let y = (bounds[1] / 2.0) - half_size;
+// Draw word wrapped text inside `rect`.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_block(color: [f32; 4], txt: &str, rect: [f64; 4], align: Align, valign: VAlign, size: u32, gc: &mut GlyphCache, c: &Context, gl: &mut GlGraphics) {
+    for line in layout(txt, size, rect, align, valign, gc) {
+        draw_text(color, &line.text, line.pos, size, gc, c, gl);
+    }
+}
 
-    draw_text(WHITE, txt, [x, y], size, gc, c, gl);
-}
\ No newline at end of file
+// Draw text centered in the window
+pub fn draw_center(txt: &str, size: u32, bounds: [f64; 2], gc: &mut GlyphCache, c: &Context, gl: &mut GlGraphics) {
+    draw_block(WHITE, txt, [0.0, 0.0, bounds[0], bounds[1]], Align::Center, VAlign::Middle, size, gc, c, gl);
+}
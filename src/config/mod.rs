@@ -1,34 +1,140 @@
-use glutin_window::GlutinWindow as Window;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+use glutin_window::{GlutinWindow as Window, UserEvent};
 use piston::window::{ Size, WindowSettings };
 use opengl_graphics::{ GlGraphics, OpenGL };
+use winit::dpi::LogicalSize;
+use winit::event_loop::EventLoopBuilder;
+use winit::window::{Fullscreen, WindowBuilder};
 
+pub mod assets;
 pub mod font;
+pub mod sprite;
+pub mod viewport;
+
+// Version asked for when none is requested explicitly, and the shader versions
+// used depending on what the driver actually gives.
+const OPENGL_DEFAULT: OpenGL = OpenGL::V3_2;
+const OPENGL_FALLBACKS: [OpenGL; 2] = [OpenGL::V3_2, OpenGL::V2_1];
+
+// Window and context options picked by the player.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DisplaySettings {
+    pub fullscreen: bool,
+    pub vsync: bool,
+    // Multisample anti-aliasing samples, 0 turns it off.
+    pub samples: u8,
+    // Require this version instead of falling back from 3.2 to 2.1.
+    pub opengl: Option<OpenGL>,
+    // Off for players who get unwell from a shaking screen.
+    pub screen_shake: bool,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> DisplaySettings {
+        DisplaySettings {
+            fullscreen: false,
+            vsync: true,
+            samples: 0,
+            opengl: None,
+            screen_shake: true,
+        }
+    }
+}
+
+pub fn parse_opengl(version: &str) -> Result<OpenGL, String> {
+    match version {
+        "2.1" => Ok(OpenGL::V2_1),
+        "3.2" => Ok(OpenGL::V3_2),
+        "3.3" => Ok(OpenGL::V3_3),
+        "4.0" => Ok(OpenGL::V4_0),
+        "4.5" => Ok(OpenGL::V4_5),
+        _ => Err(format!("Unsupported OpenGL version '{}', expected 2.1, 3.2, 3.3, 4.0 or 4.5", version)),
+    }
+}
+
 pub struct GraphicsConfig {
     // OpenGL drawing backend
     pub gl: GlGraphics,
     // Window
     pub settings: Window,
-    // Window size
+    // Window size, follows resizes. The playfield keeps the size it was created with.
     pub size: Size,
+    // Version the context was actually created with.
+    pub opengl: OpenGL,
+    pub display: DisplaySettings,
 }
 
 impl GraphicsConfig {
-    pub fn new(title: &'static str, width: f64, height: f64) -> GraphicsConfig {
-        // Change this to OpenGL::V2_1 if not working.
-        let opengl = OpenGL::V3_2;
+    pub fn new(title: &'static str, width: f64, height: f64, display: &DisplaySettings) -> Result<GraphicsConfig, String> {
         // Setup a new window
-        let size = Size { width, height };
-        let settings: Window = WindowSettings::new(title, [width, height])
+        let window_settings = WindowSettings::new(title, [width, height])
             // Sets the OpenGL version
-            .graphics_api(opengl)
+            .graphics_api(display.opengl.unwrap_or(OPENGL_DEFAULT))
             .exit_on_esc(true)
-            .build()
-            .unwrap();
+            .fullscreen(display.fullscreen)
+            .vsync(display.vsync)
+            .samples(display.samples);
 
-        GraphicsConfig {
+        // Only one event loop can ever be created, so the monitor for
+        // fullscreen is looked up on the one the window gets, and a failed
+        // window can't be retried. When the version asked for isn't available
+        // the window tries OpenGL ES and then 2.1 by itself. An ES context
+        // can't be drawn on, --opengl 2.1 asks for 2.1 straight away.
+        let event_loop = EventLoopBuilder::<UserEvent>::with_user_event().build();
+        let mut builder = WindowBuilder::new()
+            .with_inner_size(LogicalSize { width, height })
+            .with_title(title);
+        if display.fullscreen {
+            builder = builder.with_fullscreen(Some(Fullscreen::Borderless(event_loop.primary_monitor())));
+        }
+        let settings = Window::from_raw(&window_settings, event_loop, builder)
+            .map_err(|err| format!("No OpenGL context could be created ({}). \
+                                    Check your graphics drivers or try --opengl 2.1 without --msaa.", err))?;
+
+        let version = context_version().ok_or("Unable to read the OpenGL context version")?;
+        let opengl = shader_version(display.opengl, &version)?;
+        // Fullscreen windows report their real size with the first resize event.
+        Ok(GraphicsConfig {
             gl: GlGraphics::new(opengl),
             settings,
-            size
-        }
+            size: Size { width, height },
+            opengl,
+            display: *display,
+        })
+    }
+}
+
+// Version string of the current context, e.g. "3.3.0 NVIDIA 535.54".
+fn context_version() -> Option<String> {
+    // The window made its context current and loaded the functions.
+    let version = unsafe { gl::GetString(gl::VERSION) };
+    if version.is_null() {
+        return None;
     }
-}
\ No newline at end of file
+    let version = unsafe { CStr::from_ptr(version as *const c_char) };
+    version.to_str().ok().map(String::from)
+}
+
+// Shaders to draw with on a context reporting `version`: the requested
+// version if the context has it, otherwise the newest of 3.2 and 2.1 it
+// supports when nothing was requested.
+pub fn shader_version(requested: Option<OpenGL>, version: &str) -> Result<OpenGL, String> {
+    if version.starts_with("OpenGL ES") {
+        return Err(format!("Got an OpenGL ES context ({}), which can't be drawn on. Try --opengl 2.1", version));
+    }
+    let mut numbers = version.split_whitespace().next().unwrap_or("").split('.').map(|n| n.parse::<isize>());
+    let actual = match (numbers.next(), numbers.next()) {
+        (Some(Ok(major)), Some(Ok(minor))) => (major, minor),
+        _ => return Err(format!("Unrecognised OpenGL version '{}'", version)),
+    };
+
+    let supports = |opengl: OpenGL| actual >= opengl.get_major_minor();
+    match requested {
+        Some(opengl) if supports(opengl) => Ok(opengl),
+        Some(opengl) => Err(format!("OpenGL {:?} was requested but the context only has {}.{}", opengl, actual.0, actual.1)),
+        None => OPENGL_FALLBACKS.into_iter().find(|opengl| supports(*opengl))
+            .ok_or_else(|| format!("OpenGL 2.1 or newer is needed, the context only has {}.{}", actual.0, actual.1)),
+    }
+}
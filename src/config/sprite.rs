@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+
+use graphics::{Context, Image, Transformed, color::WHITE};
+use opengl_graphics::{GlGraphics, Texture};
+use serde::Deserialize;
+
+// Frames cut out of one texture, each [x, y, width, height] in pixels.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Atlas {
+    pub texture: String,
+    pub frames: BTreeMap<String, [f64; 4]>,
+}
+
+// How an object type is drawn, as written in the asset manifest. Either a
+// whole texture or a frame of an atlas.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpriteDef {
+    pub texture: Option<String>,
+    pub atlas: Option<String>,
+    pub frame: Option<String>,
+    // Multiplied with the texture colours.
+    pub tint: Option<[f32; 4]>,
+    // Degrees clockwise, for art that doesn't face up.
+    pub rotation: f64,
+    // Drawn size, defaults to the object's collision circle.
+    pub size: Option<[f64; 2]>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sprite {
+    pub texture: String,
+    // Part of the texture to draw, all of it if None.
+    pub frame: Option<[f64; 4]>,
+    pub tint: [f32; 4],
+    pub rotation: f64,
+    pub size: Option<[f64; 2]>,
+}
+
+impl SpriteDef {
+    pub fn resolve(&self, atlases: &BTreeMap<String, Atlas>) -> Result<Sprite, String> {
+        let (texture, frame) = match (&self.texture, &self.atlas) {
+            (Some(texture), None) => (texture.clone(), None),
+            (None, Some(name)) => {
+                let atlas = atlases.get(name).ok_or_else(|| format!("unknown atlas '{}'", name))?;
+                let frame_name = self.frame.as_ref().ok_or_else(|| format!("no frame of atlas '{}' given", name))?;
+                let frame = atlas.frames.get(frame_name)
+                    .ok_or_else(|| format!("atlas '{}' has no frame '{}'", name, frame_name))?;
+                (atlas.texture.clone(), Some(*frame))
+            },
+            _ => return Err("needs either a texture or an atlas".to_string()),
+        };
+
+        Ok(Sprite {
+            texture,
+            frame,
+            tint: self.tint.unwrap_or(WHITE),
+            rotation: self.rotation,
+            size: self.size,
+        })
+    }
+}
+
+// Draw `sprite` centred on `pos`, rotated by `rotation` degrees on top of the
+// sprite's own rotation.
+pub fn draw_sprite(sprite: &Sprite, texture: &Texture, pos: [f64; 2], size: [f64; 2], rotation: f64, ctxt: &Context, gl: &mut GlGraphics) {
+    let [width, height] = sprite.size.unwrap_or(size);
+    let mut image = Image::new_color(sprite.tint).rect([-width / 2.0, -height / 2.0, width, height]);
+    if let Some(frame) = sprite.frame {
+        image = image.src_rect(frame);
+    }
+
+    let transform = ctxt.transform
+        .trans(pos[0], pos[1])
+        .rot_deg(sprite.rotation + rotation);
+    image.draw(texture, &ctxt.draw_state, transform, gl);
+}
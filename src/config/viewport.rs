@@ -0,0 +1,50 @@
+use graphics::{Context, Transformed};
+use piston::window::Size;
+
+// Maps the fixed virtual playfield onto the real window, keeping its aspect
+// ratio and filling the rest of the window with bars.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Letterbox {
+    pub scale: f64,
+    // Top left corner of the playfield in window coordinates.
+    pub offset: [f64; 2],
+    pub virtual_size: Size,
+    pub window_size: [f64; 2],
+}
+
+impl Letterbox {
+    pub fn fit(virtual_size: Size, window_size: [f64; 2]) -> Letterbox {
+        let scale = (window_size[0] / virtual_size.width)
+            .min(window_size[1] / virtual_size.height)
+            .max(f64::EPSILON);
+        let offset = [
+            (window_size[0] - virtual_size.width * scale) / 2.0,
+            (window_size[1] - virtual_size.height * scale) / 2.0,
+        ];
+
+        Letterbox { scale, offset, virtual_size, window_size }
+    }
+
+    // Context that draws in virtual playfield coordinates.
+    pub fn apply(&self, c: &Context) -> Context {
+        c.trans(self.offset[0], self.offset[1]).scale(self.scale, self.scale)
+    }
+
+    // Window position (e.g. of the mouse) in playfield coordinates.
+    pub fn to_virtual(&self, pos: [f64; 2]) -> [f64; 2] {
+        [(pos[0] - self.offset[0]) / self.scale, (pos[1] - self.offset[1]) / self.scale]
+    }
+
+    // Rectangles around the playfield in window coordinates, empty ones included.
+    pub fn bars(&self) -> [[f64; 4]; 4] {
+        let [w, h] = self.window_size;
+        let [x, y] = self.offset;
+        let (pw, ph) = (self.virtual_size.width * self.scale, self.virtual_size.height * self.scale);
+        [
+            [0.0, 0.0, w, y],
+            [0.0, y + ph, w, h - y - ph],
+            [0.0, y, x, ph],
+            [x + pw, y, w - x - pw, ph],
+        ]
+    }
+}
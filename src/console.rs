@@ -0,0 +1,375 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+
+use crate::models::GameObject;
+use crate::models::enemy::{Enemy, EnemyKind, Movement};
+use crate::models::pickup::{Pickup, PickupKind};
+use crate::replay::{Playback, Replay};
+use crate::world::{World, MAX_ENEMIES};
+
+// Output lines kept for the drop-down.
+const MAX_LOG: usize = 200;
+const MAX_TIME_SCALE: f64 = 10.0;
+// Most updates a single `step` runs, so the frame it runs in still ends.
+const MAX_STEPS: u64 = 100_000;
+const DT: f64 = 1.0 / 120.0;
+
+pub const HELP: &str = "\
+spawn <basic|fast|heavy|ammo|health> [X Y] [COUNT]
+set <health|ammo|score> <VALUE>
+difficulty <N>      jump to difficulty N
+uptime <N>          jump to update N
+god [on|off]        breaches cost no health
+timescale <F>       updates per frame, 0 pauses
+step [N]            run N updates now
+dump <PATH>         write the world state to a file
+replay <PATH>       restart and play a recorded replay
+help";
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Spawn {
+    Enemy(EnemyKind),
+    Pickup(PickupKind),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Spawn { what: Spawn, pos: Option<[f64; 2]>, count: u32 },
+    Set { field: String, value: f64 },
+    Difficulty(u64),
+    Uptime(u64),
+    God(Option<bool>),
+    TimeScale(f64),
+    Step(u64),
+    Dump(PathBuf),
+    Replay(PathBuf),
+    Help,
+}
+
+impl Command {
+    // None for blank lines and `#` comments.
+    pub fn parse(line: &str) -> Result<Option<Command>, String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let args = &words[1..];
+        let command = match (words[0], args) {
+            ("spawn", [what, rest @ ..]) => {
+                let what = parse_name(what).map(Spawn::Enemy)
+                    .or_else(|_| parse_name(what).map(Spawn::Pickup))
+                    .map_err(|_| format!("Unknown thing to spawn '{}'", what))?;
+                let (pos, count) = match rest {
+                    [] => (None, 1),
+                    [count] => (None, parse(count)?),
+                    [x, y] => (Some([parse(x)?, parse(y)?]), 1),
+                    [x, y, count] => (Some([parse(x)?, parse(y)?]), parse(count)?),
+                    _ => return Err(usage("spawn")),
+                };
+                if count as usize > MAX_ENEMIES {
+                    return Err(format!("Can't spawn more than {} at once", MAX_ENEMIES));
+                }
+                Command::Spawn { what, pos, count }
+            },
+            ("set", [field, value]) => match *field {
+                "health" | "ammo" | "score" => {
+                    let value: f64 = parse(value)?;
+                    if !value.is_finite() {
+                        return Err(format!("Invalid value '{}'", value));
+                    }
+                    Command::Set { field: field.to_string(), value }
+                },
+                _ => return Err(format!("Unknown field '{}', expected health, ammo or score", field)),
+            },
+            ("difficulty", [n]) => Command::Difficulty(parse(n)?),
+            ("uptime", [n]) => Command::Uptime(parse(n)?),
+            ("god", []) => Command::God(None),
+            ("god", ["on"]) => Command::God(Some(true)),
+            ("god", ["off"]) => Command::God(Some(false)),
+            ("timescale", [scale]) => {
+                let scale: f64 = parse(scale)?;
+                if !(0.0..=MAX_TIME_SCALE).contains(&scale) {
+                    return Err(format!("Time scale must be between 0 and {}", MAX_TIME_SCALE));
+                }
+                Command::TimeScale(scale)
+            },
+            ("step", []) => Command::Step(1),
+            ("step", [n]) => {
+                let ticks: u64 = parse(n)?;
+                if ticks > MAX_STEPS {
+                    return Err(format!("Can't step more than {} updates at once", MAX_STEPS));
+                }
+                Command::Step(ticks)
+            },
+            ("dump", [path]) => Command::Dump(PathBuf::from(path)),
+            ("replay", [path]) => Command::Replay(PathBuf::from(path)),
+            ("help", []) => Command::Help,
+            (name, _) if HELP.lines().any(|line| line.split_whitespace().next() == Some(name)) => return Err(usage(name)),
+            (name, _) => return Err(format!("Unknown command '{}', try 'help'", name)),
+        };
+        Ok(Some(command))
+    }
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse::<T>().map_err(|_| format!("Invalid value '{}'", value))
+}
+
+// Names as used in level files.
+fn parse_name<T: DeserializeOwned>(name: &str) -> Result<T, serde_json::Error> {
+    serde_json::from_value(serde_json::Value::String(name.to_string()))
+}
+
+fn usage(name: &str) -> String {
+    let line = HELP.lines().find(|line| line.split_whitespace().next() == Some(name)).unwrap_or(name);
+    format!("Usage: {}", line.split("  ").next().unwrap_or(line))
+}
+
+// Drop-down developer console. Commands act on the world directly, apart
+// from the time scale and replay playback, which the frontend drives.
+pub struct Console {
+    pub open: bool,
+    // Line being typed.
+    pub line: String,
+    pub log: Vec<String>,
+    history: Vec<String>,
+    // Position while browsing the history, None when typing a new line.
+    browsing: Option<usize>,
+    // World updates per frame.
+    pub time_scale: f64,
+    pub playback: Option<Playback>,
+    // Set when a command changed or replaced the world, for the frontend to
+    // take and clear.
+    pub world_changed: bool,
+}
+
+impl Default for Console {
+    fn default() -> Console {
+        Console::new()
+    }
+}
+
+impl Console {
+    pub fn new() -> Console {
+        Console {
+            open: false,
+            line: String::new(),
+            log: Vec::new(),
+            history: Vec::new(),
+            browsing: None,
+            time_scale: 1.0,
+            playback: None,
+            world_changed: false,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+    }
+
+    pub fn print(&mut self, text: &str) {
+        self.log.extend(text.lines().map(String::from));
+        if self.log.len() > MAX_LOG {
+            self.log.drain(..self.log.len() - MAX_LOG);
+        }
+    }
+
+    pub fn type_text(&mut self, text: &str) {
+        self.line.extend(text.chars().filter(|c| *c != '`' && !c.is_control()));
+    }
+
+    pub fn backspace(&mut self) {
+        self.line.pop();
+    }
+
+    // Step through earlier lines, `back` towards the oldest.
+    pub fn browse(&mut self, back: bool) {
+        let pos = match (self.browsing, back) {
+            (None, true) => self.history.len().checked_sub(1),
+            (None, false) => None,
+            (Some(pos), true) => Some(pos.saturating_sub(1)),
+            (Some(pos), false) => Some(pos + 1).filter(|pos| *pos < self.history.len()),
+        };
+        self.browsing = pos;
+        self.line = pos.map(|pos| self.history[pos].clone()).unwrap_or_default();
+    }
+
+    // Run the typed line and log it with its result.
+    pub fn submit(&mut self, world: &mut World) {
+        let line = std::mem::take(&mut self.line);
+        self.browsing = None;
+        if line.trim().is_empty() {
+            return;
+        }
+        self.history.push(line.clone());
+        self.print(&format!("> {}", line));
+        match self.run(&line, world) {
+            Ok(output) => self.print(&output),
+            Err(msg) => self.print(&msg),
+        }
+    }
+
+    // Run every line of a script, stopping at the first failing one.
+    pub fn run_script(&mut self, path: &Path, world: &mut World) -> Result<(), String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read script '{}': {}", path.display(), err))?;
+        for (number, line) in text.lines().enumerate() {
+            let output = self.run(line, world)
+                .map_err(|msg| format!("{}:{}: {}", path.display(), number + 1, msg))?;
+            self.print(&output);
+        }
+        Ok(())
+    }
+
+    // Read commands line by line and write their results, for headless runs.
+    pub fn run_lines<R: BufRead, W: Write>(&mut self, input: R, mut output: W, world: &mut World) -> io::Result<()> {
+        for line in input.lines() {
+            match self.run(&line?, world) {
+                Ok(text) if text.is_empty() => (),
+                Ok(text) => writeln!(output, "{}", text)?,
+                Err(msg) => writeln!(output, "error: {}", msg)?,
+            }
+            for text in self.log.drain(..) {
+                writeln!(output, "{}", text)?;
+            }
+            output.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self, line: &str, world: &mut World) -> Result<String, String> {
+        match Command::parse(line)? {
+            Some(command) => self.execute(command, world),
+            None => Ok(String::new()),
+        }
+    }
+
+    pub fn execute(&mut self, command: Command, world: &mut World) -> Result<String, String> {
+        let changes_world = !matches!(command, Command::TimeScale(_) | Command::Dump(_) | Command::Help);
+        let output = self.apply(command, world)?;
+        self.world_changed |= changes_world;
+        Ok(output)
+    }
+
+    fn apply(&mut self, command: Command, world: &mut World) -> Result<String, String> {
+        match command {
+            Command::Spawn { what, pos, count } => {
+                if matches!(what, Spawn::Enemy(_)) && world.enemies.len() + count as usize > MAX_ENEMIES {
+                    return Err(format!("There can't be more than {} enemies, {} are alive", MAX_ENEMIES, world.enemies.len()));
+                }
+                let [x, y] = pos.unwrap_or([world.size.width / 2.0, 0.0]);
+                for _ in 0..count {
+                    match what {
+                        Spawn::Enemy(kind) => world.enemies.push(Enemy::with_kind(x, y, kind, Movement::Straight)),
+                        Spawn::Pickup(kind) => world.pickups.push(Pickup::new(x, y, kind)),
+                    }
+                }
+                let name = match what {
+                    Spawn::Enemy(kind) => format!("{:?}", kind),
+                    Spawn::Pickup(kind) => format!("{:?}", kind),
+                };
+                Ok(format!("Spawned {} {} at {:.0}, {:.0}", count, name.to_lowercase(), x, y))
+            },
+            Command::Set { field, value } => {
+                match field.as_str() {
+                    "health" => world.player.health = value,
+                    "ammo" => world.ammo = value.max(0.0) as u32,
+                    _ => world.score = value.max(0.0) as u32,
+                }
+                if let Some(mut director) = world.director.take() {
+                    director.restart_window(world);
+                    world.director = Some(director);
+                }
+                Ok(format!("{} = {}", field, value))
+            },
+            Command::Difficulty(difficulty) => {
+                // Past `enemy_spawn_cooldown` the endless game is won.
+                let max = world.rules.enemy_spawn_cooldown;
+                if difficulty > max {
+                    return Err(format!("Difficulty goes up to {}", max));
+                }
+                world.uptime = difficulty.checked_mul(world.rules.difficulty_interval)
+                    .ok_or_else(|| format!("Difficulty {} is out of range", difficulty))?;
+                Ok(format!("Difficulty {} (update {})", difficulty, world.uptime))
+            },
+            Command::Uptime(uptime) => {
+                world.uptime = uptime;
+                Ok(format!("Update {} (difficulty {})", uptime, world.difficulty()))
+            },
+            Command::God(on) => {
+                world.god_mode = on.unwrap_or(!world.god_mode);
+                Ok(format!("God mode {}", if world.god_mode { "on" } else { "off" }))
+            },
+            Command::TimeScale(scale) => {
+                self.time_scale = scale;
+                Ok(format!("Time scale {}", scale))
+            },
+            Command::Step(ticks) => {
+                for _ in 0..ticks {
+                    self.step(world);
+                }
+                Ok(format!("Update {}", world.uptime))
+            },
+            Command::Dump(path) => {
+                fs::write(&path, describe(world))
+                    .map_err(|err| format!("Unable to write '{}': {}", path.display(), err))?;
+                Ok(format!("World written to '{}'", path.display()))
+            },
+            Command::Replay(path) => {
+                let replay = Replay::load(&path)?;
+                replay.apply(world)?;
+                let ticks = replay.ticks;
+                self.playback = Some(Playback::new(replay));
+                Ok(format!("Playing '{}', {} updates", path.display(), ticks))
+            },
+            Command::Help => Ok(HELP.to_string()),
+        }
+    }
+
+    // One world update, fed from the replay while one is playing.
+    pub fn step(&mut self, world: &mut World) {
+        match self.playback.as_mut() {
+            Some(playback) => {
+                let before = playback.diverged;
+                playback.step(world);
+                let (diverged, finished) = (playback.diverged, playback.finished());
+                if let (None, Some(tick)) = (before, diverged) {
+                    self.print(&format!("Replay diverges at tick {}", tick));
+                }
+                if finished {
+                    self.playback = None;
+                    self.print("Replay finished");
+                }
+            },
+            None => world.update(DT),
+        }
+    }
+}
+
+// Human readable summary of the world.
+pub fn describe(world: &World) -> String {
+    let mut out = String::new();
+    let player = &world.player;
+    let _ = writeln!(out, "status {:?}, update {}, difficulty {}, seed {}, checksum {:016x}", world.game_status, world.uptime, world.difficulty(), world.seed, world.checksum());
+    let _ = writeln!(out, "score {}, highscore {}, ammo {}, shoot cooldown {}", world.score, world.highscore, world.ammo, world.shoot_cooldown);
+    let _ = writeln!(out, "player at {:.2}, {:.2} heading {:?}, health {}", player.pos.x, player.pos.y, player.dir, player.health);
+    for enemy in world.enemies.iter() {
+        let pos = enemy.position();
+        let _ = writeln!(out, "enemy {:?} at {:.2}, {:.2}, health {}", enemy.kind, pos.x, pos.y, enemy.health);
+    }
+    for bullet in world.bullets.iter() {
+        let pos = bullet.position();
+        let _ = writeln!(out, "bullet at {:.2}, {:.2}", pos.x, pos.y);
+    }
+    for pickup in world.pickups.iter() {
+        let pos = pickup.position();
+        let _ = writeln!(out, "pickup {:?} at {:.2}, {:.2}", pickup.kind, pos.x, pos.y);
+    }
+    out
+}
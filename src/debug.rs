@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant};
+
+use graphics::{line, rectangle, Context};
+use opengl_graphics::GlGraphics;
+
+use crate::models::GameObject;
+use crate::world::World;
+
+// Side of a cell of the reference grid, in pixels.
+pub const GRID_CELL: f64 = 50.0;
+const GRID_COLOR: [f32; 4] = [0.3, 0.3, 0.8, 0.25];
+const SPAWN_COLOR: [f32; 4] = [1.0, 0.5, 0.0, 0.35];
+const SPAWN_MARKER: f64 = 4.0;
+// How often the frame and update rates are recomputed.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+// Developer overlay showing what the simulation is doing under the art.
+pub struct DebugOverlay {
+    pub enabled: bool,
+    // Frames and updates counted since `since`.
+    frames: u32,
+    updates: u32,
+    since: Option<Instant>,
+    pub fps: f64,
+    pub ups: f64,
+}
+
+impl DebugOverlay {
+    pub fn new(enabled: bool) -> DebugOverlay {
+        DebugOverlay { enabled, frames: 0, updates: 0, since: None, fps: 0.0, ups: 0.0 }
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+
+    pub fn frame(&mut self, now: Instant) {
+        self.frames += 1;
+        self.roll(now);
+    }
+
+    pub fn update(&mut self, now: Instant) {
+        self.updates += 1;
+        self.roll(now);
+    }
+
+    fn roll(&mut self, now: Instant) {
+        let since = *self.since.get_or_insert(now);
+        let elapsed = now.duration_since(since);
+        if elapsed >= RATE_WINDOW {
+            let secs = elapsed.as_secs_f64();
+            self.fps = f64::from(self.frames) / secs;
+            self.ups = f64::from(self.updates) / secs;
+            self.frames = 0;
+            self.updates = 0;
+            self.since = Some(now);
+        }
+    }
+
+    // Text shown in the corner of the overlay.
+    pub fn lines(&self, world: &World) -> Vec<String> {
+        vec![
+            format!("FPS {:.0}  UPS {:.0}", self.fps, self.ups),
+            format!("Tick {}", world.uptime),
+            format!("Difficulty {}  pace {:.2}", world.difficulty(), world.pace()),
+            format!("Enemies {}  bullets {}", world.enemies.len(), world.bullets.len()),
+            format!("Pickups {}  effects {}", world.pickups.len(), world.effects.len()),
+            format!("Particles {}", world.particles.particles().len()),
+        ]
+    }
+}
+
+// Grid, spawn columns, collision shapes and velocities, in playfield coordinates.
+pub fn draw_world(world: &World, c: &Context, gl: &mut GlGraphics) {
+    let (width, height) = (world.size.width, world.size.height);
+
+    // Collisions aren't spatially partitioned, the grid is only there to
+    // judge distances by.
+    let mut x = 0.0;
+    while x <= width {
+        line(GRID_COLOR, 0.5, [x, 0.0, x, height], c.transform, gl);
+        x += GRID_CELL;
+    }
+    let mut y = 0.0;
+    while y <= height {
+        line(GRID_COLOR, 0.5, [0.0, y, width, y], c.transform, gl);
+        y += GRID_CELL;
+    }
+
+    // Path an enemy takes from each spawn point straight across the field.
+    let reach = width.max(height);
+    for point in world.spawn_points.iter() {
+        let heading = point.from.heading();
+        let end = [point.x + heading[0] * reach, point.y + heading[1] * reach];
+        line(SPAWN_COLOR, 0.5, [point.x, point.y, end[0], end[1]], c.transform, gl);
+        rectangle(SPAWN_COLOR, rectangle::centered_square(point.x, point.y, SPAWN_MARKER), c.transform, gl);
+    }
+
+    world.player.render_dbg(c, gl);
+    for enemy in world.enemies.iter() {
+        enemy.render_dbg(c, gl);
+    }
+    for bullet in world.bullets.iter() {
+        bullet.render_dbg(c, gl);
+    }
+    for pickup in world.pickups.iter() {
+        pickup.render_dbg(c, gl);
+    }
+}
@@ -0,0 +1,236 @@
+use std::fmt;
+use std::io::Write;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::models::enemy::EnemyKind;
+use crate::models::pickup::PickupKind;
+use crate::models::player::PLAYER_HEALTH;
+use crate::world::World;
+
+// Limits and tuning of the director. Every knob stays within its min/max no
+// matter how well or badly the player does.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectorConfig {
+    // Updates between two evaluations of the player.
+    pub interval: u64,
+    // How much a knob moves per evaluation, as a fraction of its range.
+    pub step: f64,
+    // Multiplier on how often waves spawn.
+    pub min_spawn_rate: f64,
+    pub max_spawn_rate: f64,
+    // Share of fast and heavy enemies in a wave.
+    pub min_special_share: f64,
+    pub max_special_share: f64,
+    // Chance of a wave dropping a pickup.
+    pub min_pickup_chance: f64,
+    pub max_pickup_chance: f64,
+}
+
+impl Default for DirectorConfig {
+    fn default() -> DirectorConfig {
+        DirectorConfig {
+            interval: 600,
+            step: 0.1,
+            min_spawn_rate: 0.6,
+            max_spawn_rate: 1.8,
+            min_special_share: 0.0,
+            max_special_share: 0.5,
+            min_pickup_chance: 0.0,
+            max_pickup_chance: 0.6,
+        }
+    }
+}
+
+// What the player did since the last evaluation.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Performance {
+    pub health_lost: f64,
+    pub shots: u32,
+    pub kills: u32,
+    pub ammo: u32,
+}
+
+impl Performance {
+    pub fn accuracy(&self) -> f64 {
+        if self.shots == 0 { 0.0 } else { f64::from(self.kills) / f64::from(self.shots) }
+    }
+
+    // How comfortably the player is doing, from -1 (struggling) to 1 (cruising).
+    pub fn rating(&self, interval: u64) -> f64 {
+        // A breach costs 100 health, losing more than two per interval is bad.
+        let health = 1.0 - (self.health_lost / (PLAYER_HEALTH / 5.0)).min(2.0);
+        let accuracy = self.accuracy() * 2.0 - 1.0;
+        // One kill every 100 updates is par.
+        let kill_rate = (f64::from(self.kills) * 100.0 / interval.max(1) as f64 - 1.0).clamp(-1.0, 1.0);
+        let ammo = (f64::from(self.ammo) / 20.0 - 1.0).clamp(-1.0, 1.0);
+
+        (0.4 * health + 0.2 * accuracy + 0.25 * kill_rate + 0.15 * ammo).clamp(-1.0, 1.0)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Knobs {
+    pub spawn_rate: f64,
+    pub special_share: f64,
+    pub pickup_chance: f64,
+}
+
+// One evaluation, kept so the director's behaviour can be audited.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Decision {
+    pub tick: u64,
+    pub performance: Performance,
+    pub rating: f64,
+    pub before: Knobs,
+    pub after: Knobs,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "tick {}: rating {:+.2} (lost {} health, {}/{} hits, {} ammo) spawn rate {:.2} -> {:.2}, \
+                   special share {:.2} -> {:.2}, pickup chance {:.2} -> {:.2}",
+            self.tick, self.rating, self.performance.health_lost, self.performance.kills,
+            self.performance.shots, self.performance.ammo,
+            self.before.spawn_rate, self.after.spawn_rate,
+            self.before.special_share, self.after.special_share,
+            self.before.pickup_chance, self.after.pickup_chance)
+    }
+}
+
+// Adjusts the endless mode to how the player is doing. It only reads the
+// world and draws from the world's generator, so a fixed seed gives the same
+// decisions every run.
+#[derive(Serialize, Deserialize)]
+pub struct Director {
+    pub config: DirectorConfig,
+    pub knobs: Knobs,
+    pub decisions: Vec<Decision>,
+    // Every decision is also written here as a JSON line, if set.
+    #[serde(skip)]
+    log: Option<Box<dyn Write>>,
+    since_wave: u64,
+    // World counters at the start of the current evaluation window.
+    window_health: f64,
+    window_shots: u32,
+    window_kills: u32,
+}
+
+impl Director {
+    pub fn new(config: DirectorConfig) -> Director {
+        let mut director = Director {
+            config,
+            knobs: Knobs { spawn_rate: 1.0, special_share: 0.0, pickup_chance: 0.0 },
+            decisions: Vec::new(),
+            log: None,
+            since_wave: 0,
+            window_health: PLAYER_HEALTH,
+            window_shots: 0,
+            window_kills: 0,
+        };
+        director.reset();
+        director
+    }
+
+    pub fn with_log(mut self, log: Box<dyn Write>) -> Director {
+        self.log = Some(log);
+        self
+    }
+
+    pub fn reset(&mut self) {
+        let config = &self.config;
+        self.knobs = Knobs {
+            spawn_rate: 1.0_f64.clamp(config.min_spawn_rate, config.max_spawn_rate),
+            special_share: config.min_special_share,
+            pickup_chance: (config.min_pickup_chance + config.max_pickup_chance) / 2.0,
+        };
+        self.decisions.clear();
+        // Let the first wave spawn straight away, like the endless mode does.
+        self.since_wave = u64::MAX;
+        self.window_health = PLAYER_HEALTH;
+        self.window_shots = 0;
+        self.window_kills = 0;
+    }
+
+    // Measure the current window from the world as it is now, e.g. after the
+    // console rewrote the score.
+    pub fn restart_window(&mut self, world: &World) {
+        self.window_health = world.player.health;
+        self.window_shots = world.shots;
+        self.window_kills = world.score;
+    }
+
+    // Evaluate the player if an interval has passed. Returns the decision taken, if any.
+    pub fn observe(&mut self, world: &World) -> Option<&Decision> {
+        if world.uptime == 0 || !world.uptime.is_multiple_of(self.config.interval.max(1)) {
+            return None;
+        }
+
+        let performance = Performance {
+            health_lost: (self.window_health - world.player.health).max(0.0),
+            shots: world.shots.saturating_sub(self.window_shots),
+            kills: world.score.saturating_sub(self.window_kills),
+            ammo: world.ammo,
+        };
+        self.window_health = world.player.health;
+        self.window_shots = world.shots;
+        self.window_kills = world.score;
+
+        let rating = performance.rating(self.config.interval);
+        let before = self.knobs;
+        let config = &self.config;
+        let nudge = |value: f64, min: f64, max: f64, direction: f64| {
+            (value + direction * config.step * (max - min)).clamp(min, max)
+        };
+
+        self.knobs = Knobs {
+            spawn_rate: nudge(before.spawn_rate, config.min_spawn_rate, config.max_spawn_rate, rating),
+            special_share: nudge(before.special_share, config.min_special_share, config.max_special_share, rating),
+            // Struggling players get more pickups.
+            pickup_chance: nudge(before.pickup_chance, config.min_pickup_chance, config.max_pickup_chance, -rating),
+        };
+
+        let decision = Decision { tick: world.uptime, performance, rating, before, after: self.knobs };
+        if let Some(log) = self.log.as_mut() {
+            let written = serde_json::to_writer(&mut *log, &decision).map_err(std::io::Error::from)
+                .and_then(|_| writeln!(log))
+                .and_then(|_| log.flush());
+            if let Err(err) = written {
+                eprintln!("Unable to write director log: {}", err);
+                self.log = None;
+            }
+        }
+        self.decisions.push(decision);
+        self.decisions.last()
+    }
+
+    // Whether a wave is due, given the interval the endless mode would use.
+    pub fn wave_due(&mut self, base_interval: u64) -> bool {
+        self.since_wave = self.since_wave.saturating_add(1);
+        let interval = (base_interval as f64 / self.knobs.spawn_rate).round().max(1.0) as u64;
+        if self.since_wave >= interval {
+            self.since_wave = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn enemy_kind<R: Rng>(&self, rng: &mut R) -> EnemyKind {
+        if rng.gen_bool(self.knobs.special_share.clamp(0.0, 1.0)) {
+            if rng.gen_bool(0.5) { EnemyKind::Fast } else { EnemyKind::Heavy }
+        } else {
+            EnemyKind::Basic
+        }
+    }
+
+    pub fn pickup<R: Rng>(&self, rng: &mut R) -> Option<PickupKind> {
+        if rng.gen_bool(self.knobs.pickup_chance.clamp(0.0, 1.0)) {
+            Some(if rng.gen_bool(0.5) { PickupKind::Ammo } else { PickupKind::Health })
+        } else {
+            None
+        }
+    }
+}
@@ -1,4 +1,7 @@
-#[derive(Copy, Clone, Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     WEST,
     NORTH,
@@ -6,7 +9,56 @@ pub enum Direction {
     SOUTH
 }
 
-#[derive(Debug)]
+impl Direction {
+    // Degrees clockwise from straight up the screen.
+    pub fn angle(self) -> f64 {
+        match self {
+            Direction::NORTH => 0.0,
+            Direction::EAST => 90.0,
+            Direction::SOUTH => 180.0,
+            Direction::WEST => 270.0,
+        }
+    }
+}
+
+// Degrees clockwise from straight up for a direction vector in screen coordinates.
+pub fn vector_angle(v: [f64; 2]) -> f64 {
+    v[0].atan2(-v[1]).to_degrees().rem_euclid(360.0)
+}
+
+// Shortest signed turn from one angle to another, in -180..180 degrees.
+pub fn angle_diff(from: f64, to: f64) -> f64 {
+    (to - from + 180.0).rem_euclid(360.0) - 180.0
+}
+
+// Rotate `p` clockwise on screen by `deg` degrees around the origin.
+pub fn rotate(p: [f64; 2], deg: f64) -> [f64; 2] {
+    let (sin, cos) = deg.to_radians().sin_cos();
+    [p[0] * cos - p[1] * sin, p[0] * sin + p[1] * cos]
+}
+
+// Whether a circle touches or overlaps a triangle.
+pub fn circle_hits_triangle(center: [f64; 2], radius: f64, tri: &[[f64; 2]; 3]) -> bool {
+    let cross = |a: [f64; 2], b: [f64; 2], p: [f64; 2]| (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0]);
+    let sides = [cross(tri[0], tri[1], center), cross(tri[1], tri[2], center), cross(tri[2], tri[0], center)];
+    let inside = sides.iter().all(|side| *side >= 0.0) || sides.iter().all(|side| *side <= 0.0);
+    if inside {
+        return true;
+    }
+
+    (0..3).any(|i| {
+        let (a, b) = (tri[i], tri[(i + 1) % 3]);
+        let ab = [b[0] - a[0], b[1] - a[1]];
+        let len = ab[0] * ab[0] + ab[1] * ab[1];
+        let t = if len == 0.0 { 0.0 } else {
+            (((center[0] - a[0]) * ab[0] + (center[1] - a[1]) * ab[1]) / len).clamp(0.0, 1.0)
+        };
+        let closest = [a[0] + ab[0] * t, a[1] + ab[1] * t];
+        (center[0] - closest[0]).powi(2) + (center[1] - closest[1]).powi(2) <= radius * radius
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Position {
     pub x: f64,
     pub y: f64
@@ -0,0 +1,349 @@
+use std::io::{self, BufRead, Write};
+
+use piston::input::{Button, Key};
+use piston::window::Size;
+use serde::{Deserialize, Serialize};
+
+use crate::models::GameObject;
+use crate::models::player::PLAYER_HEALTH;
+use crate::world::{GameStatus, World};
+
+// Number of enemies and bullets described in a feature observation. Missing
+// entities are padded with zeros.
+pub const MAX_ENEMY_FEATURES: usize = 16;
+pub const MAX_BULLET_FEATURES: usize = 8;
+pub const FEATURE_LEN: usize = 5 + 3 * (MAX_ENEMY_FEATURES + MAX_BULLET_FEATURES);
+
+// Largest side of a pixel observation.
+pub const MAX_PIXELS: usize = 4096;
+
+const DT: f64 = 1.0 / 120.0;
+
+// Discrete actions an agent can take. Movement actions hold the direction for
+// the whole step, `Noop` lets go of every key.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Noop,
+    Left,
+    Right,
+    Up,
+    Down,
+    Fire,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ObservationKind {
+    Features,
+    Pixels { width: usize, height: usize },
+}
+
+impl ObservationKind {
+    // Pixel grids need a size that can be allocated.
+    pub fn validate(&self) -> Result<(), String> {
+        if let ObservationKind::Pixels { width, height } = *self {
+            let valid = (1..=MAX_PIXELS).contains(&width)
+                && (1..=MAX_PIXELS).contains(&height)
+                && width.checked_mul(height).is_some();
+            if !valid {
+                return Err(format!("Pixel observations must be 1 to {} cells wide and high, got {}x{}", MAX_PIXELS, width, height));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Observation {
+    // Player state followed by the closest enemies and the bullets, see `Env::features`.
+    Features { values: Vec<f32> },
+    // Downscaled greyscale grid, row major: 255 player, 170 enemy, 85 bullet.
+    Pixels { width: usize, height: usize, data: Vec<u8> },
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct RewardWeights {
+    // Per enemy shot down.
+    pub score: f64,
+    // Per point of health lost, scaled by the starting health.
+    pub health: f64,
+    // Per enemy reaching the bottom of the screen.
+    pub breach: f64,
+    // Once when the player dies.
+    pub death: f64,
+}
+
+impl Default for RewardWeights {
+    fn default() -> RewardWeights {
+        RewardWeights {
+            score: 1.0,
+            health: 10.0,
+            breach: 0.5,
+            death: 10.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Info {
+    pub score: u32,
+    pub health: f64,
+    pub ammo: u32,
+    pub uptime: u64,
+    pub breaches: u32,
+    pub status: GameStatus,
+}
+
+// Reinforcement learning environment over a headless `World`.
+pub struct Env {
+    world: World,
+    pub observation: ObservationKind,
+    pub rewards: RewardWeights,
+    // Simulation updates run for every call to `step`.
+    pub ticks_per_step: u32,
+    held: Option<Key>,
+    fire_held: bool,
+}
+
+impl Env {
+    pub fn new(observation: ObservationKind) -> Env {
+        Env {
+            world: World::with_seed(Size { width: 400.0, height: 600.0 }, 0),
+            observation,
+            rewards: RewardWeights::default(),
+            ticks_per_step: 1,
+            held: None,
+            fire_held: false,
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.world.reset_with_seed(seed);
+        self.held = None;
+        self.fire_held = false;
+        self.observe()
+    }
+
+    pub fn step(&mut self, action: Action) -> (Observation, f64, bool, Info) {
+        let (score, health, breaches) = (self.world.score, self.world.player.health, self.world.breaches);
+
+        self.apply(action);
+        for _ in 0..self.ticks_per_step {
+            self.world.update(DT);
+            if self.world.game_status != GameStatus::Normal {
+                break;
+            }
+        }
+
+        let world = &self.world;
+        let mut reward = f64::from(world.score - score) * self.rewards.score
+            - (health - world.player.health).max(0.0) / PLAYER_HEALTH * self.rewards.health
+            - f64::from(world.breaches - breaches) * self.rewards.breach;
+        let done = world.game_status != GameStatus::Normal;
+        if world.game_status == GameStatus::Died && health > 0.0 {
+            reward -= self.rewards.death;
+        }
+
+        (self.observe(), reward, done, self.info())
+    }
+
+    pub fn info(&self) -> Info {
+        Info {
+            score: self.world.score,
+            health: self.world.player.health,
+            ammo: self.world.ammo,
+            uptime: self.world.uptime,
+            breaches: self.world.breaches,
+            status: self.world.game_status,
+        }
+    }
+
+    // Feed the action through the same input path a keyboard would use.
+    fn apply(&mut self, action: Action) {
+        if self.fire_held {
+            self.world.input(&Button::Keyboard(Key::Space), false);
+            self.fire_held = false;
+        }
+
+        let wanted = match action {
+            Action::Left => Some(Key::Left),
+            Action::Right => Some(Key::Right),
+            Action::Up => Some(Key::Up),
+            Action::Down => Some(Key::Down),
+            Action::Fire => self.held,
+            Action::Noop => None,
+        };
+        if wanted != self.held {
+            if let Some(key) = self.held.take() {
+                self.world.input(&Button::Keyboard(key), false);
+            }
+            if let Some(key) = wanted {
+                self.world.input(&Button::Keyboard(key), true);
+                self.held = Some(key);
+            }
+        }
+
+        if action == Action::Fire {
+            self.world.input(&Button::Keyboard(Key::Space), true);
+            self.fire_held = true;
+        }
+    }
+
+    pub fn observe(&self) -> Observation {
+        match self.observation {
+            ObservationKind::Features => Observation::Features { values: self.features() },
+            ObservationKind::Pixels { width, height } => Observation::Pixels {
+                width,
+                height,
+                data: self.pixels(width, height),
+            },
+        }
+    }
+
+    // Layout: player x, player y, health, ammo, shoot cooldown, then
+    // `MAX_ENEMY_FEATURES` enemies closest to the bottom and
+    // `MAX_BULLET_FEATURES` bullets as (x, y, present) triples. Positions are
+    // normalised to the playfield size.
+    pub fn features(&self) -> Vec<f32> {
+        let world = &self.world;
+        let (width, height) = (world.size.width, world.size.height);
+        let mut values = Vec::with_capacity(FEATURE_LEN);
+
+        values.push((world.player.pos.x / width) as f32);
+        values.push((world.player.pos.y / height) as f32);
+        values.push((world.player.health / PLAYER_HEALTH) as f32);
+        values.push(world.ammo as f32 / world.rules.start_ammo.max(1) as f32);
+        values.push(world.shoot_cooldown as f32 / world.rules.shoot_cooldown.max(1) as f32);
+
+        let mut enemies: Vec<&dyn GameObject> = world.enemies.iter().map(|e| e as &dyn GameObject).collect();
+        enemies.sort_by(|a, b| b.position().y.total_cmp(&a.position().y));
+        push_objects(&mut values, &enemies, MAX_ENEMY_FEATURES, world.size);
+
+        let bullets: Vec<&dyn GameObject> = world.bullets.iter().map(|b| b as &dyn GameObject).collect();
+        push_objects(&mut values, &bullets, MAX_BULLET_FEATURES, world.size);
+
+        values
+    }
+
+    pub fn pixels(&self, width: usize, height: usize) -> Vec<u8> {
+        let world = &self.world;
+        let mut data = vec![0u8; width * height];
+
+        for bullet in world.bullets.iter() {
+            fill(&mut data, width, height, bullet, world.size, 85);
+        }
+        for enemy in world.enemies.iter() {
+            fill(&mut data, width, height, enemy, world.size, 170);
+        }
+        fill(&mut data, width, height, &world.player, world.size, 255);
+
+        data
+    }
+}
+
+fn push_objects(values: &mut Vec<f32>, objects: &[&dyn GameObject], max: usize, size: Size) {
+    for i in 0..max {
+        match objects.get(i) {
+            Some(object) => {
+                values.push((object.position().x / size.width) as f32);
+                values.push((object.position().y / size.height) as f32);
+                values.push(1.0);
+            },
+            None => values.extend_from_slice(&[0.0, 0.0, 0.0]),
+        }
+    }
+}
+
+// Paint the bounding square of an object into the grid, at least one cell.
+fn fill(data: &mut [u8], width: usize, height: usize, object: &dyn GameObject, size: Size, value: u8) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    let (sx, sy) = (width as f64 / size.width, height as f64 / size.height);
+    let pos = object.position();
+    let r = object.radius();
+
+    let x0 = ((pos.x - r) * sx).floor().max(0.0) as usize;
+    let y0 = ((pos.y - r) * sy).floor().max(0.0) as usize;
+    let x1 = (((pos.x + r) * sx).ceil() as usize).clamp(x0 + 1, width);
+    let y1 = (((pos.y + r) * sy).ceil() as usize).clamp(y0 + 1, height);
+
+    for y in y0.min(height)..y1 {
+        for x in x0.min(width)..x1 {
+            data[y * width + x] = value;
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+enum Request {
+    Reset {
+        #[serde(default)]
+        seed: u64,
+        observation: Option<ObservationKind>,
+        ticks_per_step: Option<u32>,
+        rewards: Option<RewardWeights>,
+    },
+    Step { action: Action },
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Response {
+    Reset { observation: Observation },
+    Step { observation: Observation, reward: f64, done: bool, info: Info },
+    Error { error: String },
+}
+
+// Serve the environment over JSON lines: one request per input line, one
+// response per output line. For example
+//   {"cmd": "reset", "seed": 7, "observation": {"kind": "pixels", "width": 40, "height": 60}}
+//   {"cmd": "step", "action": "left"}
+pub fn run_json_lines<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut env = Env::new(ObservationKind::Features);
+
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(Request::Reset { seed, observation, ticks_per_step, rewards }) => {
+                match observation.as_ref().map_or(Ok(()), ObservationKind::validate) {
+                    Err(error) => Response::Error { error },
+                    Ok(()) => {
+                        if let Some(observation) = observation {
+                            env.observation = observation;
+                        }
+                        if let Some(ticks) = ticks_per_step {
+                            env.ticks_per_step = ticks.max(1);
+                        }
+                        if let Some(rewards) = rewards {
+                            env.rewards = rewards;
+                        }
+                        Response::Reset { observation: env.reset(seed) }
+                    },
+                }
+            },
+            Ok(Request::Step { action }) => {
+                let (observation, reward, done, info) = env.step(action);
+                Response::Step { observation, reward, done, info }
+            },
+            Err(err) => Response::Error { error: err.to_string() },
+        };
+
+        serde_json::to_writer(&mut output, &response)?;
+        writeln!(output)?;
+        output.flush()?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,291 @@
+use std::fs;
+use std::path::Path;
+
+use piston::window::Size;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::models::enemy::{Enemy, EnemyKind, Movement};
+use crate::spawn::{Edge, SpawnLayout, SpawnPoint};
+use crate::world::World;
+
+// A hand authored level, loaded from JSON. Wave times are in updates since
+// the level started. For example
+//
+// {
+//   "name": "Intro",
+//   "waves": [
+//     { "type": "spawn", "at": 60, "column": 4 },
+//     { "type": "spawn", "at": 300, "x": 200, "kind": "fast",
+//       "formation": "v", "count": 5, "spacing": 30 },
+//     { "type": "repeat", "at": 600, "times": 3, "every": 240, "waves": [
+//       { "type": "random", "at": 0, "chance": 0.3, "kinds": ["basic", "heavy"] }
+//     ] }
+//   ]
+// }
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Level {
+    pub name: String,
+    // Spawn points used while this level is played, the world's own if unset.
+    pub layout: Option<SpawnLayout>,
+    pub waves: Vec<Wave>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Wave {
+    // A single enemy or a formation of them.
+    Spawn {
+        at: u64,
+        #[serde(flatten)]
+        group: Group,
+    },
+    // Every spawn point rolls on its own, like the endless mode does.
+    Random {
+        at: u64,
+        chance: f32,
+        // Picked at random for every enemy, basic if empty.
+        #[serde(default)]
+        kinds: Vec<EnemyKind>,
+        #[serde(default)]
+        movement: Movement,
+    },
+    // Play `waves` `times` times, `every` updates apart.
+    Repeat {
+        at: u64,
+        times: u32,
+        every: u64,
+        waves: Vec<Wave>,
+    },
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Formation {
+    #[default]
+    Single,
+    // Side by side, all spawned at once.
+    Line,
+    // The leader in the middle, the others trailing behind on both sides.
+    V,
+    // One behind the other in the same column.
+    Column,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Group {
+    #[serde(default)]
+    pub kind: EnemyKind,
+    // Index into the spawn points, takes precedence over `x`.
+    pub column: Option<usize>,
+    // Position along the top edge.
+    pub x: Option<f64>,
+    #[serde(default)]
+    pub movement: Movement,
+    #[serde(default)]
+    pub formation: Formation,
+    #[serde(default = "default_count")]
+    pub count: u32,
+    #[serde(default = "default_spacing")]
+    pub spacing: f64,
+}
+
+fn default_count() -> u32 { 1 }
+fn default_spacing() -> f64 { 30.0 }
+
+// A wave flattened to the update it happens on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+enum Event {
+    Spawn { point: SpawnPoint, kind: EnemyKind, movement: Movement },
+    Random { chance: f32, kinds: Vec<EnemyKind>, movement: Movement },
+}
+
+impl Level {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Level, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read level {}: {}", path.display(), err))?;
+        Level::parse(&content).map_err(|err| format!("Invalid level {}: {}", path.display(), err))
+    }
+
+    pub fn parse(content: &str) -> Result<Level, String> {
+        serde_json::from_str(content).map_err(|err| err.to_string())
+    }
+
+    // Expand loops and formations into a list of events sorted by time.
+    fn schedule(&self, points: &[SpawnPoint], size: Size) -> Result<Vec<(u64, Event)>, String> {
+        let points = match self.layout.as_ref() {
+            Some(layout) => layout.points(size),
+            None => points.to_vec(),
+        };
+        let mut events = Vec::new();
+        for wave in &self.waves {
+            expand(wave, 0, &points, size, &mut events)?;
+        }
+        // Stable, so events on the same update keep their file order.
+        events.sort_by_key(|(at, _)| *at);
+        Ok(events)
+    }
+}
+
+fn expand(wave: &Wave, offset: u64, points: &[SpawnPoint], size: Size, events: &mut Vec<(u64, Event)>) -> Result<(), String> {
+    match wave {
+        Wave::Spawn { at, group } => {
+            let top = |x: f64| SpawnPoint { x, y: 0.0, from: Edge::Top };
+            let point = match (group.column, group.x) {
+                (Some(column), _) => *points.get(column)
+                    .ok_or_else(|| format!("Column {} does not exist, there are {}", column, points.len()))?,
+                (None, Some(x)) => top(x),
+                (None, None) => top(size.width / 2.0),
+            };
+            // Formations spread out sideways to the direction of travel.
+            let heading = point.from.heading();
+            let side = [heading[1], -heading[0]];
+            let count = group.count.max(1);
+            // Updates it takes the enemy to cover `spacing`.
+            let delay = (group.spacing / group.kind.speed()).round() as u64;
+            let middle = f64::from(count - 1) / 2.0;
+
+            for i in 0..count {
+                let offset_from_middle = f64::from(i) - middle;
+                let (spread, wait) = match group.formation {
+                    Formation::Single => (0.0, 0),
+                    Formation::Line => (offset_from_middle * group.spacing, 0),
+                    Formation::V => (offset_from_middle * group.spacing, offset_from_middle.abs().ceil() as u64 * delay),
+                    Formation::Column => (0.0, u64::from(i) * delay),
+                };
+                let point = SpawnPoint {
+                    x: (point.x + side[0] * spread).clamp(0.0, size.width),
+                    y: (point.y + side[1] * spread).clamp(0.0, size.height),
+                    from: point.from,
+                };
+                events.push((offset + at + wait, Event::Spawn {
+                    point,
+                    kind: group.kind,
+                    movement: group.movement,
+                }));
+            }
+        },
+        Wave::Random { at, chance, kinds, movement } => {
+            events.push((offset + at, Event::Random { chance: *chance, kinds: kinds.clone(), movement: *movement }));
+        },
+        Wave::Repeat { at, times, every, waves } => {
+            for i in 0..u64::from(*times) {
+                for inner in waves {
+                    expand(inner, offset + at + i * every, points, size, events)?;
+                }
+            }
+        },
+    }
+    Ok(())
+}
+
+// Plays a sequence of levels in place of the endless random spawns.
+#[derive(Serialize, Deserialize)]
+pub struct Campaign {
+    pub levels: Vec<Level>,
+    pub current: usize,
+    // Updates since the current level started.
+    pub tick: u64,
+    schedule: Vec<(u64, Event)>,
+    cursor: usize,
+    // The world's layout before the first level replaced it.
+    base_layout: Option<SpawnLayout>,
+}
+
+impl Campaign {
+    pub fn new(levels: Vec<Level>) -> Result<Campaign, String> {
+        if levels.is_empty() {
+            return Err("A campaign needs at least one level".to_string());
+        }
+        Ok(Campaign {
+            levels,
+            current: 0,
+            tick: 0,
+            schedule: Vec::new(),
+            cursor: 0,
+            base_layout: None,
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(paths: &[P]) -> Result<Campaign, String> {
+        let levels = paths.iter().map(Level::load).collect::<Result<Vec<_>, _>>()?;
+        Campaign::new(levels)
+    }
+
+    // Check every level can be scheduled on the given world.
+    pub fn validate(&self, world: &World) -> Result<(), String> {
+        for level in &self.levels {
+            level.schedule(&world.spawn_points, world.size)
+                .map_err(|err| format!("Level '{}': {}", level.name, err))?;
+        }
+        Ok(())
+    }
+
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.tick = 0;
+        self.schedule.clear();
+        self.cursor = 0;
+    }
+
+    pub fn level(&self) -> Option<&Level> {
+        self.levels.get(self.current)
+    }
+
+    // Spawn whatever is due this update. Returns true once the last level
+    // has been cleared.
+    pub fn step(&mut self, world: &mut World) -> bool {
+        if self.current >= self.levels.len() {
+            return true;
+        }
+
+        if self.tick == 0 {
+            // Levels are validated up front, an invalid one is simply empty.
+            let level = &self.levels[self.current];
+            let base_layout = self.base_layout.get_or_insert_with(|| world.spawn_layout.clone());
+            let layout = level.layout.as_ref().unwrap_or(base_layout);
+            if *layout != world.spawn_layout {
+                world.set_spawn_layout(layout.clone());
+            }
+            self.schedule = level.schedule(&world.spawn_points, world.size).unwrap_or_default();
+            self.cursor = 0;
+        }
+        self.tick += 1;
+
+        while let Some((at, event)) = self.schedule.get(self.cursor) {
+            if *at > self.tick {
+                break;
+            }
+            spawn(event, world);
+            self.cursor += 1;
+        }
+
+        if self.cursor >= self.schedule.len() && world.enemies.is_empty() {
+            self.current += 1;
+            self.tick = 0;
+        }
+        self.current >= self.levels.len()
+    }
+}
+
+fn spawn(event: &Event, world: &mut World) {
+    match event {
+        Event::Spawn { point, kind, movement } => {
+            world.enemies.push(Enemy::with_kind(point.x, point.y, *kind, *movement).from_edge(point.from));
+        },
+        Event::Random { chance, kinds, movement } => {
+            for point in world.spawn_points.iter() {
+                let roll: f32 = world.rng.gen();
+                if roll > 1.0 - chance {
+                    let kind = if kinds.is_empty() {
+                        EnemyKind::Basic
+                    } else {
+                        kinds[world.rng.gen_range(0..kinds.len())]
+                    };
+                    world.enemies.push(Enemy::with_kind(point.x, point.y, kind, *movement).from_edge(point.from));
+                }
+            }
+        },
+    }
+}
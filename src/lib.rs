@@ -1,135 +1,405 @@
-use std::{fs, io::Write};
+use std::{collections::HashMap, fs, io::Write, panic::{self, AssertUnwindSafe}, path::{Path, PathBuf}, time::Instant};
 
-use geom::Direction;
 use graphics::{color::BLACK, color::WHITE, color::{RED, GREEN}, color::CYAN};
 use models::GameObject;
-use models::bullet::Bullet;
-use opengl_graphics::{GlyphCache, TextureSettings};
-use piston::input::{RenderArgs, UpdateArgs, Button, Key};
-use models::player::Player;
-use models::enemy::Enemy;
-use rand::{thread_rng, Rng};
-use find_folder;
-
-use crate::config::font::draw_text;
+use graphics::Context;
+use opengl_graphics::{GlGraphics, GlyphCache, Texture, TextureSettings};
+use piston::input::{RenderArgs, ResizeArgs, UpdateArgs, Button, Key};
+use piston::window::Size;
+use world::{GameEvent, GameStatus, World};
+
+use crate::audio::{Audio, Sfx, Sound, MUSIC_GAME, MUSIC_GAME_OVER};
+use crate::background::{Background, LayerDef};
+use crate::camera::Camera;
+use crate::console::Console;
+use crate::debug::DebugOverlay;
+use crate::models::enemy::EnemyKind;
+use crate::config::assets::{AssetKind, AssetManager, HUD_FONT};
+use crate::config::font::{draw_aligned, draw_block, line_height, Align, VAlign};
+use crate::config::sprite::draw_sprite;
+use crate::config::viewport::Letterbox;
+use crate::replay::Recorder;
+use crate::save::Slots;
+use crate::snapshot::Snapshots;
+use crate::title::{MenuItem, TitleScreen};
 
 mod geom;
+pub mod anim;
+pub mod audio;
+pub mod background;
+pub mod batch;
+pub mod bot;
+pub mod camera;
+pub mod checksum;
+pub mod cli;
 pub mod config;
+pub mod console;
+pub mod debug;
+pub mod director;
+pub mod gym;
+pub mod level;
 pub mod models;
+pub mod particles;
+pub mod replay;
+pub mod save;
+pub mod snapshot;
+pub mod spawn;
+pub mod synth;
+pub mod title;
+pub mod tui;
+pub mod world;
+
+// Distance of the HUD from the playfield border.
+const HUD_MARGIN: f64 = 12.0;
+
+// Seconds between two looks for changed asset files in debug builds.
+const RELOAD_INTERVAL: f64 = 1.0;
+
+// Updates the game freezes for, and screen shake added, when the player takes
+// a hit or a big enemy goes down.
+const HITSTOP_BREACH: u32 = 6;
+const HITSTOP_HEAVY_KILL: u32 = 4;
+const TRAUMA_BREACH: f64 = 0.6;
+const TRAUMA_HEAVY_KILL: f64 = 0.35;
+const TRAUMA_KILL: f64 = 0.1;
+
+// Seconds the music takes to change over.
+const MUSIC_CROSSFADE: f64 = 1.5;
+
+// Key that shows and hides the debug overlay, and the overlay text's look.
+const DEBUG_KEY: Key = Key::F3;
+const DEBUG_TEXT_SIZE: u32 = 10;
+const DEBUG_PANEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+
+// Key that writes a snapshot of the world.
+const SNAPSHOT_KEY: Key = Key::F12;
+
+// Key that opens the developer console, and the console's look.
+const CONSOLE_KEY: Key = Key::Backquote;
+const CONSOLE_TEXT_SIZE: u32 = 10;
+const CONSOLE_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.85];
+// Share of the playfield height the console drops down over.
+const CONSOLE_HEIGHT: f64 = 0.4;
+
+// Key that pauses the game and brings up the menu, and the menu's look.
+const MENU_KEY: Key = Key::P;
+const MENU_TEXT_SIZE: u32 = 16;
+const MENU_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.75];
+
+// Colour of the bars around the playfield when the window has a different aspect ratio.
+const LETTERBOX_COLOR: [f32; 4] = [0.08, 0.08, 0.08, 1.0];
 
-const SHOOT_COOLDOWN: u32 = 50;
-const ENEMY_SPAWN_COOLDOWN: u64 = 500;
-
-#[derive(PartialEq)]
-enum GameStatus {
-    Normal,
-    Win,
-    Died
-}
 pub struct App<'a>{
     pub window: config::GraphicsConfig, // OpenGL drawing backend.
     glyph_cache: GlyphCache<'a>,
-    player: Player,
-    game_status: GameStatus,
-    enemies: Vec<Enemy>,
-    bullets: Vec<Bullet>,
-    enemy_spawn_columns: Vec<f64>,
-    fire_bullet: bool,
-    score: u32,
-    ammo: u32,
-    highscore: u32,
-    shoot_cooldown: u32,
-    uptime: u64,
+    // None when there is no usable assets folder, the built in font is used then.
+    assets: Option<AssetManager>,
+    textures: HashMap<String, Texture>,
+    since_reload: f64,
+    pub camera: Camera,
+    pub debug: DebugOverlay,
+    pub console: Console,
+    // Where snapshots go, taken with F12, on panic and periodically.
+    pub snapshots: Snapshots,
+    // World updates owed to the console's time scale.
+    sim_owed: f64,
+    // Title screen or pause menu, the world stands still while it is open.
+    pub title: Option<TitleScreen>,
+    slots: Slots,
+    // Set when the player chose to quit, the frontend closes the window then.
+    pub quit: bool,
+    // Inputs being recorded, and where the replay is saved on shutdown.
+    recording: Option<(Recorder, PathBuf)>,
+    // Updates left to freeze the game for.
+    hitstop: u32,
+    audio: Audio,
+    // Seed of the generated sound effects used where the assets have none.
+    sound_seed: u64,
+    world: World,
 }
 
 impl<'a> App<'a> {
-    pub fn new(window: config::GraphicsConfig) -> App<'a> {
-        let size = window.size;
+    // `assets` overrides where the assets folder is looked for.
+    pub fn new(window: config::GraphicsConfig, assets: Option<&Path>) -> App<'a> {
+        let mut world = World::new(window.size);
+
+        let assets = match config::assets::find_assets(assets).and_then(|root| AssetManager::open(&root)) {
+            Ok(manager) => {
+                if let Err(msg) = manager.check() {
+                    eprintln!("{}", msg);
+                }
+                Some(manager)
+            },
+            Err(msg) => {
+                eprintln!("{}, using the built in assets", msg);
+                None
+            }
+        };
 
-        let (x, y) = (f64::from(size.width / 2.0),
-                      f64::from(size.height / 1.25));
+        // Load font(s) used in the game.
+        let glyph_cache = config::assets::load_font(assets.as_ref().and_then(|manager| manager.path(AssetKind::Font, HUD_FONT)));
 
-        let player = Player::new(x, y);
+        world.highscore = load_highscore();
 
-        // Load font(s) used in the game.
-        let assets = find_folder::Search::ParentsThenKids(3, 3).for_folder("assets").unwrap();
-        let glyph_cache = GlyphCache::new(assets.join("fonts/PxPlus_IBM_VGA8.ttf"), (), TextureSettings::new())
-        .expect("Unable to load font");
+        let camera = Camera::new(window.display.screen_shake);
+        let mut app = App {
+            glyph_cache,
+            window,
+            assets,
+            textures: HashMap::new(),
+            since_reload: 0.0,
+            camera,
+            debug: DebugOverlay::new(false),
+            console: Console::new(),
+            snapshots: Snapshots::default(),
+            sim_owed: 0.0,
+            title: None,
+            slots: Slots::default(),
+            quit: false,
+            recording: None,
+            hitstop: 0,
+            audio: Audio::silent(),
+            sound_seed: synth::DEFAULT_SEED,
+            world,
+        };
+        let names: Vec<String> = app.assets.as_ref()
+            .map(|manager| manager.names(AssetKind::Texture).into_iter().map(String::from).collect())
+            .unwrap_or_default();
+        for name in names {
+            app.load_texture(&name);
+        }
+        app.apply_background();
+        app.load_sounds();
+        app
+    }
+
+    // Play sound through `audio` from now on.
+    pub fn set_audio(&mut self, audio: Audio) {
+        self.audio = audio;
+        self.load_sounds();
+    }
+
+    // Generate the default sound effects from another seed.
+    pub fn set_sound_seed(&mut self, seed: u64) {
+        self.sound_seed = seed;
+        self.load_sounds();
+    }
 
-        let mut i = size.width / 10.0;
-        let mut enemy_spawn_columns: Vec<f64> = Vec::new();
-        while i < size.width
-        {
-            enemy_spawn_columns.push(i);
-            i += size.width / 10.0;
+    fn load_sounds(&mut self) {
+        let names = Sfx::ALL.iter().map(|sfx| sfx.name()).chain([MUSIC_GAME, MUSIC_GAME_OVER]);
+        for name in names {
+            self.load_sound(name);
         }
-        
-        let file_content = match fs::read_to_string("data/highscore.txt") {
-            Ok(input) => input,
-            Err(error_msg) => {
-                println!("{:?}", error_msg);
-                let empty_string: String = String::new();
-                empty_string
+    }
+
+    // Sound effects missing from the assets are generated instead.
+    fn load_sound(&mut self, name: &str) {
+        let Some(bytes) = self.assets.as_ref().and_then(|manager| manager.sound(name)) else {
+            if let Some(sfx) = Sfx::ALL.into_iter().find(|sfx| sfx.name() == name) {
+                self.audio.set_sound(name, synth::sfx_sound(sfx, self.sound_seed));
             }
+            return;
         };
-        
-        let highscore: u32 = match file_content.parse::<u32>() {
-            Ok(score) => score,
-            Err(err_msg) => {
-                println!("{:?}", err_msg);
-                0
+        match Sound::from_wav(bytes) {
+            Ok(sound) => self.audio.set_sound(name, sound),
+            Err(err) => eprintln!("Unable to load sound '{}': {}", name, err),
+        }
+    }
+
+    // Record every input from now on, saved as a replay to `path` on shutdown.
+    pub fn record(&mut self, path: PathBuf) -> Result<(), String> {
+        self.recording = Some((Recorder::new(&self.world)?, path));
+        Ok(())
+    }
+
+    // The world was replaced outside of the recorded inputs, start the
+    // recording over from it so the replay still plays the same game.
+    fn restart_recording(&mut self) {
+        if let Some((_, path)) = self.recording.take() {
+            match self.record(path) {
+                Ok(()) => println!("Recording restarted from the new game"),
+                Err(msg) => eprintln!("{}, recording stopped", msg),
             }
+        }
+    }
+
+    // Show the title screen. While a game is running it can be resumed or
+    // saved from there.
+    pub fn open_title(&mut self) {
+        let in_game = self.world.game_status == GameStatus::Normal && self.world.uptime > 0;
+        self.title = Some(TitleScreen::new(in_game, &self.slots));
+    }
+
+    fn choose(&mut self, item: MenuItem) {
+        let slot = self.title.as_ref().map_or(0, |title| title.slot);
+        let result = match item {
+            MenuItem::Resume => Ok(()),
+            MenuItem::NewGame => {
+                self.world.reset();
+                self.restart_recording();
+                Ok(())
+            },
+            MenuItem::Continue => self.slots.load(slot).map(|world| self.continue_from(world)),
+            MenuItem::SaveAndQuit => self.slots.save(slot, &self.world).map(|()| self.quit = true),
+            MenuItem::Quit => {
+                self.quit = true;
+                Ok(())
+            },
         };
+        match result {
+            Ok(()) => {
+                self.title = None;
+                self.camera.reset();
+                self.hitstop = 0;
+            },
+            Err(msg) => {
+                eprintln!("{}", msg);
+                if let Some(title) = self.title.as_mut() {
+                    title.message = Some(msg);
+                }
+            },
+        }
+    }
 
-        App {
-            glyph_cache,
-            window,
-            player,
-            game_status: GameStatus::Normal,
-            enemies: Vec::new(),
-            bullets: Vec::new(),
-            enemy_spawn_columns,
-            fire_bullet: false,
-            shoot_cooldown: SHOOT_COOLDOWN,
-            ammo: 20,
-            score: 0,
-            highscore,
-            uptime: 0,
+    // Carry on with a saved game. The best highscore of the two is kept.
+    pub fn continue_from(&mut self, world: World) {
+        let highscore = self.world.highscore;
+        self.world = world;
+        self.world.highscore = self.world.highscore.max(highscore);
+        self.apply_background();
+        self.restart_recording();
+    }
+
+    // Flush whatever the audio backend still holds, and save the recording.
+    pub fn shutdown(&mut self) {
+        if let Err(msg) = self.audio.finish() {
+            eprintln!("{}", msg);
+        }
+        if let Some((recorder, path)) = self.recording.take() {
+            match recorder.replay.save(&path) {
+                Ok(()) => println!("Replay written to '{}'", path.display()),
+                Err(msg) => eprintln!("{}", msg),
+            }
+        }
+    }
+
+    // Use the background layers from the manifest, if it has any.
+    fn apply_background(&mut self) {
+        if let Some(defs) = self.assets.as_ref().and_then(|manager| manager.background()) {
+            self.world.background = Background::new(defs, self.world.seed);
+        }
+    }
+
+    pub fn texture(&self, name: &str) -> Option<&Texture> {
+        self.textures.get(name)
+    }
+
+    fn load_texture(&mut self, name: &str) {
+        let Some(path) = self.assets.as_ref().and_then(|manager| manager.path(AssetKind::Texture, name)) else {
+            self.textures.remove(name);
+            return;
+        };
+
+        match Texture::from_path(path, &TextureSettings::new()) {
+            Ok(texture) => {
+                self.textures.insert(name.to_string(), texture);
+            },
+            Err(err) => {
+                eprintln!("Unable to load texture '{}': {}", path.display(), err);
+                self.textures.remove(name);
+            }
         }
     }
 
+    // Pick up asset files edited while the game runs.
+    fn reload_assets(&mut self) {
+        let changed = match self.assets.as_mut().map(|manager| manager.reload_changed()) {
+            Some(Ok(changed)) => changed,
+            Some(Err(msg)) => {
+                eprintln!("{}", msg);
+                return;
+            },
+            None => return,
+        };
+
+        for id in changed {
+            match id.kind {
+                AssetKind::Font if id.name == HUD_FONT => {
+                    let path = self.assets.as_ref().and_then(|manager| manager.path(AssetKind::Font, HUD_FONT));
+                    self.glyph_cache = config::assets::load_font(path);
+                },
+                AssetKind::Font => (),
+                AssetKind::Texture => self.load_texture(&id.name),
+                AssetKind::Sound => self.load_sound(&id.name),
+            }
+            println!("Reloaded {:?} '{}'", id.kind, id.name);
+        }
+        self.apply_background();
+    }
+
     pub fn render(&mut self, args: &RenderArgs) {
         use graphics::*;
 
-        self.window.gl.draw(args.viewport(), |c, gl| {
+        self.debug.frame(Instant::now());
+        let world = &self.world;
+        let (assets, textures, camera, debug, console, title) = (self.assets.as_ref(), &self.textures, &self.camera, &self.debug, &self.console, self.title.as_ref());
+        let letterbox = Letterbox::fit(world.size, args.window_size);
+        self.window.gl.draw(args.viewport(), |window_c, gl| {
             // Clear the screen.
             clear(BLACK, gl);
 
-            match self.game_status {
+            // Everything below is drawn in playfield coordinates.
+            let c = letterbox.apply(&window_c);
+            // The game itself is seen through the camera, the HUD isn't.
+            let view = camera.apply(&c, world.size);
+            draw_background(&world.background, world.size, textures, &view, gl);
+
+            match world.game_status {
                 GameStatus::Normal => {
-                    self.player.render(&c, gl);
-                    for enemy in self.enemies.iter() {
-                        enemy.render(&c, gl);
+                    for particle in world.particles.particles() {
+                        let size = particle.size();
+                        let square = rectangle::centered_square(particle.pos[0], particle.pos[1], size / 2.0);
+                        rectangle(particle.color(), square, view.transform, gl);
                     }
-        
-                    for bullet in self.bullets.iter() {
-                        bullet.render(&c, gl);
+
+                    draw_object(&world.player, assets, textures, &view, gl);
+                    for enemy in world.enemies.iter() {
+                        draw_object(enemy, assets, textures, &view, gl);
                     }
         
-                    let curr_score = format!("Score: {:?}", self.score);
-                    draw_text(WHITE, curr_score.as_str(), [12.0, 24.0], 12, &mut self.glyph_cache, &c, gl);
+                    for bullet in world.bullets.iter() {
+                        draw_object(bullet, assets, textures, &view, gl);
+                    }
+
+                    for pickup in world.pickups.iter() {
+                        draw_object(pickup, assets, textures, &view, gl);
+                    }
+
+                    for effect in world.effects.iter() {
+                        draw_object(effect, assets, textures, &view, gl);
+                    }
         
-                    let curr_score = format!("Health: {:?}", self.player.health);
-                    draw_text(WHITE, curr_score.as_str(), [self.window.size.width - 125.0, 24.0], 12, &mut self.glyph_cache, &c, gl);
+                    let (width, height) = (world.size.width, world.size.height);
+                    let (left, right) = (HUD_MARGIN, width - HUD_MARGIN);
+                    let (top, bottom) = (HUD_MARGIN + 12.0, height - HUD_MARGIN);
 
+                    let curr_score = format!("Score: {:?}", world.score);
+                    draw_aligned(WHITE, curr_score.as_str(), [left, top], Align::Left, 12, &mut self.glyph_cache, &c, gl);
 
-                    let ammo = format!("Bullets: {:?}", self.ammo);
-                    draw_text(WHITE, ammo.as_str(), [self.window.size.width - 125.0, self.window.size.height - 24.0], 12, &mut self.glyph_cache, &c, gl);
+                    if let Some(level) = world.campaign.as_ref().and_then(|campaign| campaign.level()) {
+                        draw_aligned(WHITE, level.name.as_str(), [left, bottom], Align::Left, 12, &mut self.glyph_cache, &c, gl);
+                    }
+
+                    let health = format!("Health: {:?}", world.player.health);
+                    draw_aligned(WHITE, health.as_str(), [right, top], Align::Right, 12, &mut self.glyph_cache, &c, gl);
+
+                    let ammo = format!("Bullets: {:?}", world.ammo);
+                    draw_aligned(WHITE, ammo.as_str(), [right, bottom], Align::Right, 12, &mut self.glyph_cache, &c, gl);
                 },
                 GameStatus::Died | GameStatus::Win => {
                     let color;
                     let state;
-                    if self.game_status == GameStatus::Died {
+                    if world.game_status == GameStatus::Died {
                         color = RED;
                         state = "DEAD";
                     }
@@ -138,191 +408,329 @@ impl<'a> App<'a> {
                         state = "WIN";
                     }
 
-                    draw_text(color, state, [self.window.size.width / 3.0, self.window.size.height / 2.0 - 32.0], 32, &mut self.glyph_cache, &c, gl);
-                    let curr_score = format!("Score: {:?}", self.score);
-                    let high_score = format!("Highscore: {:?}", self.highscore);
-                    draw_text(WHITE, curr_score.as_str(), [self.window.size.width / 3.0, self.window.size.height / 2.0 + 18.0], 18, &mut self.glyph_cache, &c,  gl);
-                    draw_text(WHITE, high_score.as_str(), [self.window.size.width / 3.0, self.window.size.height / 2.0 + 40.0], 18, &mut self.glyph_cache, &c, gl);
-                    draw_text(CYAN, "Click to restart", [self.window.size.width / 3.0, self.window.size.height / 2.0 + 100.0], 18, &mut self.glyph_cache, &c, gl);
+                    let (width, height) = (world.size.width, world.size.height);
+                    let middle = width / 2.0;
+                    draw_aligned(color, state, [middle, height / 2.0 - 32.0], Align::Center, 32, &mut self.glyph_cache, &c, gl);
+                    let scores = format!("Score: {:?}\nHighscore: {:?}", world.score, world.highscore);
+                    draw_block(WHITE, scores.as_str(), [0.0, height / 2.0, width, height / 4.0], Align::Center, VAlign::Top, 18, &mut self.glyph_cache, &c, gl);
+                    draw_aligned(CYAN, "Click to restart", [middle, height / 2.0 + 100.0], Align::Center, 18, &mut self.glyph_cache, &c, gl);
                 }
             }
+
+            if let Some(title) = title {
+                let (width, height) = (world.size.width, world.size.height);
+                rectangle(MENU_COLOR, [0.0, 0.0, width, height], c.transform, gl);
+                draw_aligned(WHITE, "SPACE GAME", [width / 2.0, height / 3.0], Align::Center, 32, &mut self.glyph_cache, &c, gl);
+                let mut text = title.lines().join("\n");
+                if let Some(message) = title.message.as_ref() {
+                    text = format!("{}\n\n{}", text, message);
+                }
+                let block = [width / 6.0, height / 3.0 + 30.0, width * 2.0 / 3.0, height / 2.0];
+                draw_block(WHITE, text.as_str(), block, Align::Left, VAlign::Top, MENU_TEXT_SIZE, &mut self.glyph_cache, &c, gl);
+                let hint = "Up/Down choose, Left/Right slot, Return select";
+                draw_aligned(CYAN, hint, [width / 2.0, height - HUD_MARGIN], Align::Center, 10, &mut self.glyph_cache, &c, gl);
+            }
+
+            if debug.enabled {
+                debug::draw_world(world, &view, gl);
+                let text = debug.lines(world).join("\n");
+                let panel = [HUD_MARGIN, HUD_MARGIN + 20.0, 180.0, 90.0];
+                rectangle(DEBUG_PANEL_COLOR, panel, c.transform, gl);
+                let inner = [panel[0] + 4.0, panel[1] + 4.0, panel[2] - 8.0, panel[3] - 8.0];
+                draw_block(WHITE, text.as_str(), inner, Align::Left, VAlign::Top, DEBUG_TEXT_SIZE, &mut self.glyph_cache, &c, gl);
+            }
+
+            if console.open {
+                let panel = [0.0, 0.0, world.size.width, world.size.height * CONSOLE_HEIGHT];
+                rectangle(CONSOLE_COLOR, panel, c.transform, gl);
+                let rows = ((panel[3] - 8.0) / line_height(CONSOLE_TEXT_SIZE)) as usize;
+                let prompt = format!("> {}_", console.line);
+                let start = console.log.len().saturating_sub(rows.saturating_sub(1));
+                let text = console.log[start..].iter().map(String::as_str).chain([prompt.as_str()]).collect::<Vec<_>>().join("\n");
+                let inner = [4.0, 4.0, panel[2] - 8.0, panel[3] - 8.0];
+                draw_block(WHITE, text.as_str(), inner, Align::Left, VAlign::Bottom, CONSOLE_TEXT_SIZE, &mut self.glyph_cache, &c, gl);
+            }
+
+            // Cover whatever was drawn outside the playfield.
+            for bar in letterbox.bars() {
+                rectangle(LETTERBOX_COLOR, bar, window_c.transform, gl);
+            }
         });
 
     }
 
     pub fn update(&mut self, args: &UpdateArgs) {
-        if self.game_status != GameStatus::Normal {
-            return;
-        };
-        
-        self.uptime += 1;
-        
-        // Update Players health
-        for enemy in self.enemies.iter_mut() {
-            enemy.update(args.dt, self.window.size);
-            if enemy.defense_breached {
-                self.player.health -= enemy.health;
-                enemy.health = 0.0;
+        if cfg!(debug_assertions) {
+            self.since_reload += args.dt;
+            if self.since_reload >= RELOAD_INTERVAL {
+                self.since_reload = 0.0;
+                self.reload_assets();
             }
         }
-        
-        // If players health becomes zero: Dead.
-        if self.player.health <= 0.0
-        {
-            self.game_status = GameStatus::Died;
-            self.update_highscore();
+
+        self.debug.update(Instant::now());
+        self.camera.update();
+        if let Err(msg) = self.audio.advance(args.dt) {
+            eprintln!("{}, continuing without sound", msg);
+            self.audio = Audio::silent();
+        }
+        if self.title.is_some() {
             return;
         }
-        
-        // Update players parameters as other updattions use this parameters.
-        // self.rotation += self.speed * args.dt;
-        self.player.update(args.dt, self.window.size);
-
-        let mut difficulty = self.uptime / 50; // We increase difficulty after every interval.
-        
-        // If someone survives till difficulty 500, declare win.
-        if difficulty > ENEMY_SPAWN_COOLDOWN
-        {
-            self.game_status = GameStatus::Win;
-            self.update_highscore();
+        if self.hitstop > 0 {
+            self.hitstop -= 1;
             return;
         }
 
-        // Max difficulty is spawning enemies after every 100 updates.
-        // Hence we cannot have difficulty less more than 400.
-        if difficulty > (ENEMY_SPAWN_COOLDOWN - 100) {
-            difficulty = ENEMY_SPAWN_COOLDOWN - 100;
+        // The console's time scale decides how many world updates this one gets.
+        self.sim_owed += self.console.time_scale;
+        while self.sim_owed >= 1.0 {
+            self.sim_owed -= 1.0;
+            self.step_world(args.dt);
+            if self.hitstop > 0 {
+                self.sim_owed = 0.0;
+            }
         }
+    }
 
-        self.spawn_enemies(difficulty);
+    fn step_world(&mut self, dt: f64) {
+        let previous_highscore = self.world.highscore;
+        let playing = self.console.playback.is_some();
+        let (console, world) = (&mut self.console, &mut self.world);
+        let step = panic::catch_unwind(AssertUnwindSafe(|| {
+            if console.playback.is_some() {
+                console.step(world);
+            } else {
+                world.update(dt);
+            }
+        }));
+        // Keep the world as the panic left it, then carry on unwinding.
+        if let Err(cause) = step {
+            self.snapshot("panic");
+            panic::resume_unwind(cause);
+        }
+        if !playing {
+            if let Some((recorder, _)) = self.recording.as_mut() {
+                recorder.tick(&self.world);
+            }
+        } else if self.console.playback.is_none() {
+            // Played updates aren't recorded, carry on from where the replay ended.
+            self.restart_recording();
+        }
+        match self.snapshots.tick(&self.world) {
+            Ok(Some(path)) => println!("Snapshot written to '{}'", path.display()),
+            Ok(None) => (),
+            Err(msg) => eprintln!("{}", msg),
+        }
+        if self.world.highscore > previous_highscore {
+            save_highscore(self.world.highscore);
+        }
 
-        // Update shoot cooldown and fire bullet if reloaded.
-        if self.shoot_cooldown > 0 {
-            self.shoot_cooldown -= 1;
+        self.audio.handle(&self.world.events);
+        if self.world.game_status == GameStatus::Normal && self.audio.has_sound(MUSIC_GAME) {
+            self.audio.play_music(MUSIC_GAME, MUSIC_CROSSFADE);
         }
 
-        if self.fire_bullet && self.ammo > 0 {
-            self.fire_bullet = false;
-            self.bullets.push(Bullet::new(self.player.pos.x, self.player.pos.y));
-            self.ammo -= 1;
+        for event in self.world.events.iter() {
+            match *event {
+                GameEvent::Breach { .. } => {
+                    self.camera.add_trauma(TRAUMA_BREACH);
+                    self.hitstop = self.hitstop.max(HITSTOP_BREACH);
+                },
+                GameEvent::Kill { kind: EnemyKind::Heavy, .. } => {
+                    self.camera.add_trauma(TRAUMA_HEAVY_KILL);
+                    self.hitstop = self.hitstop.max(HITSTOP_HEAVY_KILL);
+                },
+                GameEvent::Kill { .. } => self.camera.add_trauma(TRAUMA_KILL),
+                GameEvent::Shot | GameEvent::Pickup { .. } | GameEvent::GameOver { .. } => (),
+            }
+        }
+        if self.world.game_status != GameStatus::Normal {
+            self.camera.reset();
         }
+    }
 
-        // Check for hits.
-        for bullet in self.bullets.iter_mut()
-        {
-            for enemy in self.enemies.iter_mut() {
-                if enemy.health == 0.0 {
-                    continue;
+    pub fn input (&mut self, button: &Button, press_event: bool) {
+        if *button == Button::Keyboard(CONSOLE_KEY) {
+            if press_event {
+                self.console.toggle();
+            }
+            return;
+        }
+        // The open console takes all keys, typed text arrives through `text`.
+        if self.console.open {
+            if let (Button::Keyboard(key), true) = (*button, press_event) {
+                match key {
+                    Key::Return => {
+                        self.console.submit(&mut self.world);
+                        // Console commands aren't part of the recording.
+                        if std::mem::take(&mut self.console.world_changed) {
+                            self.restart_recording();
+                        }
+                    },
+                    Key::Backspace => self.console.backspace(),
+                    Key::Up => self.console.browse(true),
+                    Key::Down => self.console.browse(false),
+                    _ => (),
                 }
-                if true == bullet.collides(enemy) {
-                    enemy.health = 0.0;
-                    bullet.destroy = true;
-                    self.score += 1;
-                    self.ammo += 2;
-                    break;
+            }
+            return;
+        }
+        if *button == Button::Keyboard(DEBUG_KEY) {
+            if press_event {
+                self.debug.toggle();
+            }
+            return;
+        }
+        if *button == Button::Keyboard(SNAPSHOT_KEY) {
+            if press_event {
+                self.snapshot("key");
+            }
+            return;
+        }
+        if let Some(title) = self.title.as_mut() {
+            if let (Button::Keyboard(key), true) = (*button, press_event) {
+                if let Some(item) = title.input(key) {
+                    self.choose(item);
                 }
             }
+            return;
         }
+        if *button == Button::Keyboard(MENU_KEY) {
+            if press_event {
+                self.open_title();
+            }
+            return;
+        }
+        // A playing replay is the only input.
+        if self.console.playback.is_some() {
+            return;
+        }
+        if let Some((recorder, _)) = self.recording.as_mut() {
+            recorder.input(button, press_event);
+        }
+        self.world.input(button, press_event);
+    }
 
-        self.enemies.retain(|enemy| enemy.health > 0.0);
-
-        self.bullets.retain(|bullet| false == bullet.destroy);
-        for bullet in self.bullets.iter_mut() {
-            bullet.update(args.dt, self.window.size);
-        } 
+    pub fn snapshot(&self, reason: &str) {
+        match self.snapshots.take(&self.world, reason) {
+            Ok(path) => println!("Snapshot written to '{}'", path.display()),
+            Err(msg) => eprintln!("{}", msg),
+        }
     }
 
-    fn update_highscore(&mut self) {
-        let str_to_write;
-        if self.score > self.highscore {
-            str_to_write = format!("{:?}", self.score);
-            self.highscore = self.score;
+    // Run a console script against the world, e.g. at startup.
+    pub fn run_script(&mut self, path: &Path) -> Result<(), String> {
+        self.console.run_script(path, &mut self.world)
+    }
 
-            match fs::create_dir("data") {
-                Ok(_) => (),
-                Err(err_msg) => println!("{:?}", err_msg),
-            }
-            
-            match fs::File::create("data/highscore.txt") {
-                Ok(mut file) => {
-                    match file.write_all(str_to_write.as_bytes()) {
-                        Ok(_) => (),
-                        Err(err_msg) => println!("{:?}", err_msg),
-                    }
-                },
-                Err(err_msg) => println!("{:?}", err_msg),
-            }
-           
+    pub fn text(&mut self, text: &str) {
+        if self.console.open {
+            self.console.type_text(text);
         }
     }
 
-    fn spawn_enemies (&mut self, difficulty: u64) {
-        if self.uptime % (ENEMY_SPAWN_COOLDOWN - difficulty) == 1
-        {
-            let mut rng = thread_rng(); 
-            let arr: [f32; 10] = rng.gen(); // Every column has its own random number.
-            for i in 0..9 {
-                if arr[i] > 0.7 {
-                    self.enemies.push(Enemy::new(self.enemy_spawn_columns[i], 0.0));
-                }
-            }
-        }
+    // The playfield keeps its size, only the way it is scaled to the window changes.
+    pub fn resize(&mut self, args: &ResizeArgs) {
+        self.window.size = args.window_size.into();
     }
 
-    fn reset_game(&mut self) {
-        self.player.reset(self.window.size.width / 2.0, self.window.size.height / 1.25);
-        self.enemies.clear();
-        self.bullets.clear();
-        self.game_status = GameStatus::Normal;
-        self.fire_bullet = false;
-        self.shoot_cooldown = SHOOT_COOLDOWN;
-        self.ammo = 20;
-        self.score = 0;
-        self.uptime = 0;
+    pub fn world(&self) -> &World {
+        &self.world
     }
 
-    pub fn input (&mut self, button: &Button, press_event: bool) {
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+}
 
+fn draw_background(background: &Background, size: Size, textures: &HashMap<String, Texture>, c: &Context, gl: &mut GlGraphics) {
+    use graphics::*;
 
-        // Restart game on mouse click after it has ended.
-        if !press_event {
-            if let Button::Mouse(_) = *button {
-                if self.game_status != GameStatus::Normal {
-                    self.reset_game();
+    for layer in background.layers.iter() {
+        match &layer.def {
+            LayerDef::Stars { color, .. } => {
+                for [x, y, star] in layer.points(size) {
+                    rectangle(*color, rectangle::centered_square(x, y, star / 2.0), c.transform, gl);
                 }
-                return;
-            }
-        }
-
-        let mut direction: Direction = self.player.dir.clone();
-        if let Button::Keyboard(key) = *button {
-            match key {
-                Key::Up => direction = geom::Direction::NORTH,
-                Key::Down => direction = geom::Direction::SOUTH,
-                Key::Right => direction = geom::Direction::EAST,
-                Key::Left => direction = geom::Direction::WEST,
-                Key::Space => {
-                    if self.shoot_cooldown <= 0 && press_event{
-                        self.fire_bullet = true;
-                    }
-                    return;
-                }, // Fire bullets!
-                _ => (), // Ignore all other
-            }
+            },
+            LayerDef::Nebula { color, .. } => {
+                for [x, y, blob] in layer.points(size) {
+                    ellipse(*color, ellipse::circle(x, y, blob / 2.0), c.transform, gl);
+                }
+            },
+            LayerDef::Texture { texture, tint, .. } => {
+                let Some(texture) = textures.get(texture) else {
+                    continue;
+                };
+                // Stretch to the playfield width and repeat downwards.
+                let (tex_width, tex_height) = texture.get_size();
+                let height = size.width * f64::from(tex_height) / f64::from(tex_width.max(1));
+                if height <= 0.0 {
+                    continue;
+                }
+                let mut y = layer.offset.rem_euclid(height) - height;
+                while y < size.height {
+                    Image::new_color(*tint)
+                        .rect([0.0, y, size.width, height])
+                        .draw(texture, &c.draw_state, c.transform, gl);
+                    y += height;
+                }
+            },
         }
+    }
+}
 
-        // print!("direction key: {:?} press_action: {:?} player direction: {:?}\n", direction, press_event, self.player.dir);
+// Draw an object with its sprite if the assets have art for it, with its
+// primitives otherwise.
+fn draw_object(object: &dyn GameObject, assets: Option<&AssetManager>, textures: &HashMap<String, Texture>, c: &Context, gl: &mut GlGraphics) {
+    let art = object.sprite()
+        .and_then(|name| assets?.sprite(name))
+        .and_then(|sprite| Some((sprite, textures.get(&sprite.texture)?)));
+
+    match art {
+        Some((sprite, texture)) => {
+            let pos = object.position();
+            let size = object.radius() * 2.0;
+            draw_sprite(sprite, texture, [pos.x, pos.y], [size, size], object.angle(), c, gl);
+        },
+        None => object.render(c, gl),
+    }
+}
 
-        if direction == self.player.dir && !press_event {
-            self.player.stop_movement = true;
+pub fn load_highscore() -> u32 {
+    let file_content = match fs::read_to_string("data/highscore.txt") {
+        Ok(input) => input,
+        Err(error_msg) => {
+            println!("{:?}", error_msg);
+            String::new()
         }
-        else if press_event {
-            self.player.stop_movement = false;               
-        }
-        if press_event {
-            self.player.stop_movement = false;
-            self.player.movement(direction);
+    };
+
+    match file_content.parse::<u32>() {
+        Ok(score) => score,
+        Err(err_msg) => {
+            println!("{:?}", err_msg);
+            0
         }
+    }
+}
+
+pub fn save_highscore(score: u32) {
+    let str_to_write = format!("{:?}", score);
 
+    match fs::create_dir_all("data") {
+        Ok(_) => (),
+        Err(err_msg) => println!("{:?}", err_msg),
+    }
+
+    match fs::File::create("data/highscore.txt") {
+        Ok(mut file) => {
+            match file.write_all(str_to_write.as_bytes()) {
+                Ok(_) => (),
+                Err(err_msg) => println!("{:?}", err_msg),
+            }
+        },
+        Err(err_msg) => println!("{:?}", err_msg),
     }
 }
@@ -5,26 +5,204 @@ extern crate graphics;
 extern crate opengl_graphics;
 extern crate piston;
 
-use piston::{ReleaseEvent, PressEvent};
+use std::{env, fs, io, process};
+use std::path::{Path, PathBuf};
+
+use piston::{ReleaseEvent, PressEvent, TextEvent, Window};
 use piston::event_loop::{EventSettings, Events};
-use piston::input::{RenderEvent, UpdateEvent};
+use piston::input::{RenderEvent, ResizeEvent, UpdateEvent};
+use piston::window::Size;
+use basic_game::audio::{Audio, AudioBackend, NullBackend, PipeBackend, Sfx, WavFileBackend};
+use basic_game::bot::{self, Bot};
+use basic_game::cli::Options;
 use basic_game::config::GraphicsConfig;
+use basic_game::console::Console;
+use basic_game::config::assets::ASSETS_ENV;
+use basic_game::director::{Director, DirectorConfig};
+use basic_game::gym;
+use basic_game::level::Campaign;
+use basic_game::replay::{self, Replay};
+use basic_game::snapshot::{self, Snapshots};
+use basic_game::synth;
+use basic_game::world::World;
 use basic_game::App;
 
+// Players the sound is piped into when no --audio-cmd is given, as 32 bit
+// float mono samples at 44100 Hz.
+const DEVICE_PLAYERS: [&str; 3] = [
+    "pw-cat --playback --format f32 --rate 44100 --channels 1 -",
+    "paplay --raw --format=float32le --rate=44100 --channels=1",
+    "aplay -q -f FLOAT_LE -r 44100 -c 1",
+];
+
 fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            process::exit(2);
+        }
+    };
+
+    if let Some(dir) = options.export_sounds.as_ref() {
+        if let Err(msg) = export_sounds(dir, options.sound_seed) {
+            eprintln!("{}", msg);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(path) = options.verify_replay.as_ref() {
+        let result = Replay::load(path).and_then(|replay| replay::verify(&replay).map(|_| replay.ticks));
+        match result {
+            Ok(ticks) => println!("Replay matches the recording for all {} updates", ticks),
+            Err(msg) => {
+                eprintln!("{}", msg);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if options.gym {
+        let stdin = io::stdin();
+        if let Err(err) = gym::run_json_lines(stdin.lock(), io::stdout().lock()) {
+            eprintln!("Gym session failed: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    let campaign = if options.levels.is_empty() {
+        None
+    } else {
+        match Campaign::load(&options.levels) {
+            Ok(campaign) => Some(campaign),
+            Err(msg) => {
+                eprintln!("{}", msg);
+                process::exit(2);
+            }
+        }
+    };
+
+    if options.headless {
+        let mut world = match loaded_world(&options) {
+            Some(world) => world,
+            None => {
+                let mut world = World::new(Size { width: 400.0, height: 600.0 });
+                world.set_spawn_layout(options.spawn_layout.clone());
+                set_campaign(&mut world, campaign);
+                world.director = director(&options);
+                world
+            }
+        };
+        let mut snapshots = Snapshots::new(&options.snapshot_dir, options.snapshot_every);
+
+        let mut console = Console::new();
+        if let Some(path) = options.script.as_ref() {
+            if let Err(msg) = console.run_script(path, &mut world) {
+                eprintln!("{}", msg);
+                process::exit(2);
+            }
+            for line in console.log.drain(..) {
+                println!("{}", line);
+            }
+        }
+        if options.console {
+            let stdin = io::stdin();
+            if let Err(err) = console.run_lines(stdin.lock(), io::stdout().lock(), &mut world) {
+                eprintln!("Console session failed: {}", err);
+                process::exit(1);
+            }
+            return;
+        }
+
+        let result = bot::soak_with(&mut world, options.ticks, |world| {
+            if let Err(msg) = snapshots.tick(world) {
+                eprintln!("{}", msg);
+            }
+        });
+        match result {
+            Ok(report) => println!("{:?}", report),
+            Err(msg) => {
+                eprintln!("Soak test failed: {}", msg);
+                match snapshots.take(&world, "failure") {
+                    Ok(path) => eprintln!("Snapshot written to '{}'", path.display()),
+                    Err(msg) => eprintln!("{}", msg),
+                }
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
     // Create a new game and run it.
-    let mut app = App::new(GraphicsConfig::new("space_game", 400.0, 600.0));
+    let window = match GraphicsConfig::new("space_game", 400.0, 600.0, &options.display) {
+        Ok(window) => window,
+        Err(msg) => {
+            eprintln!("{}", msg);
+            process::exit(1);
+        }
+    };
+    let assets = options.assets.clone()
+        .or_else(|| env::var_os(ASSETS_ENV).map(PathBuf::from));
+    let mut app = App::new(window, assets.as_deref());
+    app.debug.enabled = options.debug;
+    app.snapshots = Snapshots::new(&options.snapshot_dir, options.snapshot_every);
+    let loaded = loaded_world(&options);
+    let start_in_game = loaded.is_some() || options.bot || options.script.is_some();
+    match loaded {
+        Some(world) => app.continue_from(world),
+        None => {
+            app.world_mut().set_spawn_layout(options.spawn_layout.clone());
+            set_campaign(app.world_mut(), campaign);
+            app.world_mut().director = director(&options);
+        }
+    }
+    if let Some(path) = options.script.as_ref() {
+        if let Err(msg) = app.run_script(path) {
+            eprintln!("{}", msg);
+            process::exit(2);
+        }
+    }
+    // The replay starts from the world as set up so far, script included.
+    if let Some(path) = options.record.clone() {
+        if let Err(msg) = app.record(path) {
+            eprintln!("{}", msg);
+            process::exit(2);
+        }
+    }
+    app.set_sound_seed(options.sound_seed);
+    app.set_audio(Audio::new(audio_backend(&options), options.volumes));
+    let mut bot = if options.bot { Some(Bot::new()) } else { None };
+    // Players start at the title screen, the bot, scripts and loaded worlds go straight in.
+    if !start_in_game {
+        app.open_title();
+    }
 
     let mut events = Events::new(EventSettings::new());
     while let Some(e) = events.next(&mut app.window.settings) {
+        if let Some(args) = e.resize_args() {
+            app.resize(&args);
+        }
+
         if let Some(args) = e.render_args() {
             app.render(&args);
         }
 
         if let Some(args) = e.update_args() {
+            if let Some(bot) = bot.as_mut() {
+                for (button, press) in bot.act(app.world()) {
+                    app.input(&button, press);
+                }
+            }
             app.update(&args);
         }
 
+        if let Some(text) = e.text_args() {
+            app.text(&text);
+        }
+
         if let Some(args) = e.press_args() {
             app.input(&args, true);
         }
@@ -32,5 +210,89 @@ fn main() {
         if let Some(args) = e.release_args() {
             app.input(&args, false);
         }
+
+        if app.quit {
+            app.window.settings.set_should_close(true);
+        }
+    }
+    app.shutdown();
+}
+
+// Where the game's sound goes: an external player, a WAV file, or nowhere.
+// There is no built in device output, without --audio-cmd or --audio-out the
+// first of the usual command line players that starts gets the sound.
+fn audio_backend(options: &Options) -> Box<dyn AudioBackend> {
+    if let Some(command) = options.audio_cmd.as_ref() {
+        match PipeBackend::spawn(command) {
+            Ok(backend) => return Box::new(backend),
+            Err(msg) => eprintln!("{}, playing without sound", msg),
+        }
+    }
+    if let Some(path) = options.audio_out.as_ref() {
+        return match WavFileBackend::create(path.clone()) {
+            Ok(backend) => Box::new(backend),
+            Err(msg) => {
+                eprintln!("{}, playing without sound", msg);
+                Box::new(NullBackend::default())
+            },
+        };
+    }
+    if options.audio_cmd.is_none() && !options.no_audio {
+        if let Some(backend) = DEVICE_PLAYERS.iter().find_map(|command| PipeBackend::spawn(command).ok()) {
+            return Box::new(backend);
+        }
+        eprintln!("No audio player found (tried pw-cat, paplay and aplay), playing without sound");
+    }
+    Box::new(NullBackend::default())
+}
+
+fn export_sounds(dir: &Path, seed: u64) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|err| format!("Unable to create '{}': {}", dir.display(), err))?;
+    for sfx in Sfx::ALL {
+        let path = dir.join(format!("{}.wav", sfx.name()));
+        fs::write(&path, synth::sfx_sound(sfx, seed).to_wav())
+            .map_err(|err| format!("Unable to write '{}': {}", path.display(), err))?;
+        println!("{}", path.display());
+    }
+    Ok(())
+}
+
+// The world from --load, if given.
+fn loaded_world(options: &Options) -> Option<World> {
+    let path = options.load.as_ref()?;
+    match snapshot::load(path) {
+        Ok(world) => Some(world),
+        Err(msg) => {
+            eprintln!("{}", msg);
+            process::exit(2);
+        }
+    }
+}
+
+fn set_campaign(world: &mut World, campaign: Option<Campaign>) {
+    if let Some(campaign) = campaign.as_ref() {
+        if let Err(msg) = campaign.validate(world) {
+            eprintln!("{}", msg);
+            process::exit(2);
+        }
+    }
+    world.campaign = campaign;
+}
+
+fn director(options: &Options) -> Option<Director> {
+    if !options.director {
+        return None;
+    }
+
+    let director = Director::new(DirectorConfig::default());
+    match options.director_log.as_ref() {
+        Some(path) => match fs::OpenOptions::new().create(true).append(true).open(path) {
+            Ok(file) => Some(director.with_log(Box::new(io::LineWriter::new(file)))),
+            Err(err) => {
+                eprintln!("Unable to open director log {}: {}", path, err);
+                process::exit(2);
+            }
+        },
+        None => Some(director),
     }
 }
@@ -1,4 +1,5 @@
 use graphics::{ellipse, Transformed, color};
+use serde::{Deserialize, Serialize};
 
 use crate::geom::Position;
 
@@ -7,6 +8,7 @@ use super::GameObject;
 const BULLET_SIZE: f64 = 3.0;
 const BULLET_SPEED: f64 = 2.0;
 
+#[derive(Serialize, Deserialize)]
 pub struct Bullet {
     pos: Position,
     pub destroy: bool,
@@ -26,6 +28,7 @@ impl Bullet {
 impl GameObject for Bullet {
     fn position(&self) -> &Position { &self.pos }
     fn radius(&self) -> f64 { self.size }
+    fn sprite(&self) -> Option<&str> { Some("bullet") }
 
     fn render(&self, ctxt: &graphics::Context, gl: &mut opengl_graphics::GlGraphics) {
         let transform = ctxt.transform.trans(self.pos.x, self.pos.y);
@@ -33,6 +36,11 @@ impl GameObject for Bullet {
         ellipse(color::RED, [0.0, 0.0, radius, radius], transform, gl);
     }
 
+    fn render_dbg(&self, ctxt: &graphics::Context, gl: &mut opengl_graphics::GlGraphics) {
+        super::draw_bounds(self, ctxt, gl);
+        super::draw_velocity(&self.pos, [0.0, -BULLET_SPEED], ctxt, gl);
+    }
+
     fn update(&mut self, _dt: f64, size: piston::Size) {
         self.pos.y -= BULLET_SPEED;
 
@@ -0,0 +1,60 @@
+use graphics::{circle_arc, Transformed};
+
+use crate::anim::{Animation, Easing, Frame, Playback, Tween};
+use crate::geom::Position;
+
+use super::GameObject;
+
+const EXPLOSION_TICKS: u32 = 30;
+const EXPLOSION_SIZE: f64 = 30.0;
+
+// Short lived decoration that doesn't take part in the game.
+pub struct Effect {
+    pos: Position,
+    color: [f32; 4],
+    animation: Animation,
+    scale: Tween<f64>,
+    alpha: Tween<f32>,
+}
+
+impl Effect {
+    // Expanding, fading ring where an enemy was shot down.
+    pub fn explosion(x: f64, y: f64, color: [f32; 4]) -> Effect {
+        let frames = (0..4)
+            .map(|i| Frame::new(&format!("explosion_{}", i), EXPLOSION_TICKS / 4))
+            .collect();
+        Effect {
+            pos: Position::new(x, y),
+            color,
+            animation: Animation::new(frames, Playback::Once),
+            scale: Tween::new(0.2, 1.0, EXPLOSION_TICKS, Easing::CubicOut),
+            alpha: Tween::new(1.0, 0.0, EXPLOSION_TICKS, Easing::QuadIn),
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.animation.finished() && self.scale.done() && self.alpha.done()
+    }
+}
+
+impl GameObject for Effect {
+    fn position(&self) -> &Position { &self.pos }
+    fn radius(&self) -> f64 { EXPLOSION_SIZE * self.scale.value() / 2.0 }
+    fn sprite(&self) -> Option<&str> {
+        self.animation.frame().map(|frame| frame.sprite.as_str())
+    }
+
+    fn render(&self, ctxt: &graphics::Context, gl: &mut opengl_graphics::GlGraphics) {
+        let radius = self.radius();
+        let mut color = self.color;
+        color[3] *= self.alpha.value();
+        let transform = ctxt.transform.trans(self.pos.x, self.pos.y);
+        circle_arc(color, 2.0, 0.0, std::f64::consts::TAU, [-radius, -radius, radius * 2.0, radius * 2.0], transform, gl);
+    }
+
+    fn update(&mut self, _dt: f64, _size: piston::Size) {
+        self.animation.step();
+        self.scale.step();
+        self.alpha.step();
+    }
+}
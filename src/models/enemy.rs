@@ -1,40 +1,132 @@
 use graphics::{Context, rectangle, Transformed, color::WHITE};
 use opengl_graphics::GlGraphics;
+use serde::{Deserialize, Serialize};
 use crate::geom::{self, Position};
+use crate::spawn::Edge;
 
 use super::GameObject;
 const ENEMY_SIZE: f64 = 20.0;
 const ENEMY_SPEED: f64 = 1.0;
 const ENEMY_HEALTH: f64 = 100.0;
 
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnemyKind {
+    #[default]
+    Basic,
+    // Small and quick, does less damage when it gets through.
+    Fast,
+    // Big and slow, does a lot of damage when it gets through.
+    Heavy,
+}
+
+impl EnemyKind {
+    // Size, speed and health (which is also the breach damage) of the kind.
+    fn stats(self) -> (f64, f64, f64) {
+        match self {
+            EnemyKind::Basic => (ENEMY_SIZE, ENEMY_SPEED, ENEMY_HEALTH),
+            EnemyKind::Fast => (14.0, 2.0, 50.0),
+            EnemyKind::Heavy => (30.0, 0.5, 250.0),
+        }
+    }
+
+    pub fn speed(self) -> f64 {
+        self.stats().1
+    }
+
+    fn sprite(self) -> &'static str {
+        match self {
+            EnemyKind::Basic => "enemy_basic",
+            EnemyKind::Fast => "enemy_fast",
+            EnemyKind::Heavy => "enemy_heavy",
+        }
+    }
+
+    pub fn color(self) -> [f32; 4] {
+        match self {
+            EnemyKind::Basic => WHITE,
+            EnemyKind::Fast => [1.0, 1.0, 0.0, 1.0],
+            EnemyKind::Heavy => [0.6, 0.6, 1.0, 1.0],
+        }
+    }
+}
+
+// How an enemy moves sideways while it falls.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Movement {
+    #[default]
+    Straight,
+    // Sway around the spawn column, `period` is in updates.
+    Sine { amplitude: f64, period: f64 },
+    // Drift sideways by `dx` per update, bouncing off the screen edges.
+    Diagonal { dx: f64 },
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Enemy {
     pos: Position,
     pub defense_breached: bool,
     pub health: f64,
+    pub kind: EnemyKind,
+    pub movement: Movement,
     size: f64,
+    speed: f64,
+    // Unit vector the enemy travels along.
+    heading: [f64; 2],
+    // Sideways offset from the straight path, see `Movement`.
+    lateral: f64,
+    age: u64,
 }
 
 impl Enemy {
     pub fn new (x: f64, y: f64) -> Enemy {
+        Enemy::with_kind(x, y, EnemyKind::Basic, Movement::Straight)
+    }
+
+    pub fn with_kind (x: f64, y: f64, kind: EnemyKind, movement: Movement) -> Enemy {
+        let (size, speed, health) = kind.stats();
         Enemy {
             pos: geom::Position::new(x,y),
             defense_breached: false,
-            health: ENEMY_HEALTH,
-            size: ENEMY_SIZE,
+            health,
+            kind,
+            movement,
+            size,
+            speed,
+            heading: Edge::Top.heading(),
+            lateral: 0.0,
+            age: 0,
         }
     }
+
+    // Enter from `edge` instead of the top of the screen.
+    pub fn from_edge(mut self, edge: Edge) -> Enemy {
+        self.heading = edge.heading();
+        self
+    }
+
+    pub fn heading(&self) -> [f64; 2] {
+        self.heading
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
 }
 
 impl GameObject for Enemy {
     fn position(&self) -> &geom::Position { &self.pos }
     fn radius(&self) -> f64 { self.size / 2.0 }
+    fn angle(&self) -> f64 { geom::vector_angle(self.heading) }
+    fn sprite(&self) -> Option<&str> { Some(self.kind.sprite()) }
 
     fn render(&self, ctxt: &Context, gl: &mut GlGraphics) {
-        // Render the player as a little square
+        // Render the enemy as a little square
         let shape = rectangle::square(0.0, 0.0, self.size);
 
-        // Rotate the player to the direction they're facing
-        let dir = 90.0;
+        // Rotate the enemy to the direction it's travelling
+        let dir = self.angle();
 
         let radius = self.radius();
         let transform = ctxt.transform
@@ -42,17 +134,47 @@ impl GameObject for Enemy {
             .rot_deg(dir)
             .trans(-radius, -radius);
 
-        rectangle(WHITE, shape, transform, gl);
+        rectangle(self.kind.color(), shape, transform, gl);
+    }
+
+    fn render_dbg(&self, ctxt: &Context, gl: &mut GlGraphics) {
+        super::draw_bounds(self, ctxt, gl);
+        let vel = [self.heading[0] * self.speed, self.heading[1] * self.speed];
+        super::draw_velocity(&self.pos, vel, ctxt, gl);
     }
 
     fn update(&mut self, _dt: f64, size: piston::Size) {
-        // print!("{:?} {:?} \n", (self.dir), self.pos);
-        self.pos.y += ENEMY_SPEED;
+        self.age += 1;
+        self.pos.x += self.heading[0] * self.speed;
+        self.pos.y += self.heading[1] * self.speed;
+
+        // Sideways is to the left of the heading, which is +x for enemies falling down.
+        let side = [self.heading[1], -self.heading[0]];
+        let offset = match self.movement {
+            Movement::Straight => 0.0,
+            Movement::Sine { amplitude, period } => {
+                let phase = self.age as f64 / period.max(1.0) * std::f64::consts::TAU;
+                amplitude * phase.sin() - self.lateral
+            },
+            Movement::Diagonal { dx } => dx,
+        };
+        self.lateral += offset;
+        self.pos.x += side[0] * offset;
+        self.pos.y += side[1] * offset;
 
-        if self.pos.y < 0.0 || self.pos.y >= size.height
+        // Keep sideways movement on screen, bouncing diagonal movers.
+        let (along, limit) = if side[0] != 0.0 { (&mut self.pos.x, size.width) } else { (&mut self.pos.y, size.height) };
+        if *along <= 0.0 || *along >= limit {
+            *along = along.clamp(0.0, limit);
+            if let Movement::Diagonal { dx } = self.movement {
+                self.movement = Movement::Diagonal { dx: -dx };
+            }
+        }
+
+        if self.pos.y < 0.0 || self.pos.y >= size.height || self.pos.x < 0.0 || self.pos.x > size.width
         {
             self.defense_breached = true;
         }
        
     }
-}
\ No newline at end of file
+}
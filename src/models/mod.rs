@@ -4,9 +4,17 @@ use opengl_graphics::GlGraphics;
 use piston::window::Size;
 use crate::geom::Position;
 
+// Debug drawing: collision shapes, and velocities stretched to show where an
+// object will be this many updates from now.
+const DEBUG_SHAPE_COLOR: [f32; 4] = [0.0, 1.0, 0.0, 0.8];
+const DEBUG_VELOCITY_COLOR: [f32; 4] = [1.0, 1.0, 0.0, 0.8];
+const DEBUG_VELOCITY_SCALE: f64 = 15.0;
+
 pub mod player;
 pub mod enemy;
 pub mod bullet;
+pub mod effect;
+pub mod pickup;
 
 // Every object that needs to be rendered on screen.
 pub trait GameObject {
@@ -30,10 +38,38 @@ pub trait GameObject {
     fn position(&self) -> &Position;
     fn radius(&self) -> f64;
 
+    // Rendered rotation in degrees clockwise from facing up the screen.
+    fn angle(&self) -> f64 { 0.0 }
+    // Name of the sprite drawn instead of `render` when the assets have one.
+    fn sprite(&self) -> Option<&str> { None }
     // Main draw function for this GameObject.
     fn render(&self, ctxt: &Context, gl: &mut GlGraphics);
     // Only call if debug mode is turned on.
     fn render_dbg(&self, _: &Context, _: &mut GlGraphics) {}
     // Handle updates to movement/animation/etc.
     fn update(&mut self, _: f64, _: Size) {}
-}
\ No newline at end of file
+}
+// Outline of the collision circle most objects use.
+pub fn draw_bounds(object: &dyn GameObject, ctxt: &Context, gl: &mut GlGraphics) {
+    let pos = object.position();
+    let radius = object.radius();
+    CircleArc::new(DEBUG_SHAPE_COLOR, 0.5, 0.0, std::f64::consts::TAU - 0.0001)
+        .draw(ellipse::circle(pos.x, pos.y, radius), &ctxt.draw_state, ctxt.transform, gl);
+}
+
+// Outline of a polygon collision shape.
+pub fn draw_outline(points: &[[f64; 2]], ctxt: &Context, gl: &mut GlGraphics) {
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        line(DEBUG_SHAPE_COLOR, 0.5, [a[0], a[1], b[0], b[1]], ctxt.transform, gl);
+    }
+}
+
+// Movement per update as a line from `pos`.
+pub fn draw_velocity(pos: &Position, vel: [f64; 2], ctxt: &Context, gl: &mut GlGraphics) {
+    if vel == [0.0, 0.0] {
+        return;
+    }
+    let end = [pos.x + vel[0] * DEBUG_VELOCITY_SCALE, pos.y + vel[1] * DEBUG_VELOCITY_SCALE];
+    line(DEBUG_VELOCITY_COLOR, 0.5, [pos.x, pos.y, end[0], end[1]], ctxt.transform, gl);
+}
@@ -0,0 +1,72 @@
+use graphics::{ellipse, Transformed, color};
+use serde::{Deserialize, Serialize};
+
+use crate::geom::Position;
+
+use super::GameObject;
+
+const PICKUP_SIZE: f64 = 10.0;
+const PICKUP_SPEED: f64 = 0.75;
+pub const PICKUP_AMMO: u32 = 5;
+pub const PICKUP_HEALTH: f64 = 100.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PickupKind {
+    Ammo,
+    Health,
+}
+
+// Falls down the screen like an enemy, but is collected by touching it.
+#[derive(Serialize, Deserialize)]
+pub struct Pickup {
+    pos: Position,
+    pub kind: PickupKind,
+    pub collected: bool,
+    pub missed: bool,
+}
+
+impl Pickup {
+    pub fn new (x: f64, y: f64, kind: PickupKind) -> Pickup {
+        Pickup {
+            pos: Position::new(x, y),
+            kind,
+            collected: false,
+            missed: false,
+        }
+    }
+}
+
+impl GameObject for Pickup {
+    fn position(&self) -> &Position { &self.pos }
+    fn radius(&self) -> f64 { PICKUP_SIZE / 2.0 }
+    fn sprite(&self) -> Option<&str> {
+        Some(match self.kind {
+            PickupKind::Ammo => "pickup_ammo",
+            PickupKind::Health => "pickup_health",
+        })
+    }
+
+    fn render(&self, ctxt: &graphics::Context, gl: &mut opengl_graphics::GlGraphics) {
+        let radius = self.radius();
+        let transform = ctxt.transform.trans(self.pos.x - radius, self.pos.y - radius);
+        let color = match self.kind {
+            PickupKind::Ammo => color::CYAN,
+            PickupKind::Health => color::GREEN,
+        };
+        ellipse(color, [0.0, 0.0, PICKUP_SIZE, PICKUP_SIZE], transform, gl);
+    }
+
+    fn render_dbg(&self, ctxt: &graphics::Context, gl: &mut opengl_graphics::GlGraphics) {
+        super::draw_bounds(self, ctxt, gl);
+        super::draw_velocity(&self.pos, [0.0, PICKUP_SPEED], ctxt, gl);
+    }
+
+    fn update(&mut self, _dt: f64, size: piston::Size) {
+        self.pos.y += PICKUP_SPEED;
+
+        if self.pos.y >= size.height {
+            self.missed = true;
+        }
+    }
+}
@@ -1,5 +1,7 @@
-use graphics::{Context, polygon, Transformed, color};
+use graphics::{Context, polygon, color};
 use opengl_graphics::GlGraphics;
+use serde::{Deserialize, Serialize};
+use crate::anim::{Animation, Easing, Frame, Lerp, Playback, Tween};
 use crate::geom::{self, restrict_to_bounds};
 use crate::geom::Direction;
 
@@ -7,14 +9,46 @@ use super::GameObject;
 
 const PLAYER_SPEED: f64 = 1.75;
 const PLAYER_SIZE: f64 = 25.0;
-const PLAYER_HEALTH: f64 = 1000.0;
-
+pub const PLAYER_HEALTH: f64 = 1000.0;
+// Updates the ship flashes for after taking damage.
+const HIT_FLASH: u32 = 20;
+const THRUSTER_COLOR: [f32; 4] = [1.0, 0.6, 0.1, 1.0];
+// Share of the remaining turn made each update.
+const TURN_RATE: f64 = 0.2;
+// Degrees of turning per update that give full bank, on top of the bank from
+// moving sideways.
+const FULL_BANK_TURN: f64 = 10.0;
+// Share of the way to the target bank made each update.
+const BANK_RATE: f64 = 0.15;
+// How much narrower the ship looks at full bank.
+const BANK_NARROWING: f64 = 0.4;
+
+// The animations are only for show and start over when loaded.
+#[derive(Serialize, Deserialize)]
 pub struct Player {
     pub pos: geom::Position,
     pub dir: geom::Direction,
     pub stop_movement: bool,
     pub health: f64,
     pub size: f64,
+    #[serde(skip, default = "thruster")]
+    thruster: Animation,
+    // Goes from 1 to 0 after a hit, 1 draws the ship fully white.
+    #[serde(skip, default = "no_flash")]
+    flash: Tween<f32>,
+    // Degrees clockwise from facing up, eased towards the heading.
+    pub angle: f64,
+    // -1 (banking left) to 1 (banking right) while moving sideways or turning.
+    pub bank: f64,
+}
+
+fn thruster() -> Animation {
+    let frames = vec![Frame::new("thruster_0", 3), Frame::new("thruster_1", 2), Frame::new("thruster_2", 3)];
+    Animation::new(frames, Playback::Loop)
+}
+
+fn no_flash() -> Tween<f32> {
+    Tween::at(0.0)
 }
 
 impl Player {
@@ -25,7 +59,50 @@ impl Player {
             stop_movement: true,
             health: PLAYER_HEALTH,
             size: PLAYER_SIZE,
+            thruster: thruster(),
+            flash: no_flash(),
+            angle: 0.0,
+            bank: 0.0,
+        }
+    }
+
+    // Where the ship wants to face: its heading while moving, otherwise up
+    // the screen where it shoots.
+    pub fn target_angle(&self) -> f64 {
+        if self.stop_movement { 0.0 } else { self.dir.angle() }
+    }
+
+    // Point given relative to the ship facing up, turned and banked like the ship.
+    fn to_world(&self, p: [f64; 2]) -> [f64; 2] {
+        let narrowed = [p[0] * (1.0 - BANK_NARROWING * self.bank.abs()), p[1]];
+        let [x, y] = geom::rotate(narrowed, self.angle);
+        [self.pos.x + x, self.pos.y + y]
+    }
+
+    // The ship's triangle in playfield coordinates, also used for collisions.
+    pub fn hull(&self) -> [[f64; 2]; 3] {
+        let radius = self.radius();
+        [[0.0, -radius], [radius, radius], [-radius, radius]].map(|p| self.to_world(p))
+    }
+
+    fn turn(&mut self) {
+        let turn = geom::angle_diff(self.angle, self.target_angle()) * TURN_RATE;
+        self.angle = (self.angle + turn).rem_euclid(360.0);
+        let sideways = self.velocity()[0] / PLAYER_SPEED;
+        let target = (sideways + turn / FULL_BANK_TURN).clamp(-1.0, 1.0);
+        self.bank += (target - self.bank) * BANK_RATE;
+    }
+
+    // Distance moved per update.
+    pub fn velocity(&self) -> [f64; 2] {
+        if self.stop_movement {
+            return [0.0, 0.0];
         }
+        geom::rotate([0.0, -PLAYER_SPEED], self.dir.angle())
+    }
+
+    pub fn hit(&mut self) {
+        self.flash = Tween::new(1.0, 0.0, HIT_FLASH, Easing::QuadOut);
     }
 
     pub fn movement (&mut self, dir: Direction) {
@@ -39,45 +116,60 @@ impl Player {
         self.pos.y = y;
         self.stop_movement = true;
         self.health = PLAYER_HEALTH;
+        self.thruster.restart();
+        self.flash = no_flash();
+        self.angle = 0.0;
+        self.bank = 0.0;
     }
 }
 
 impl GameObject for Player {
     fn position(&self) -> &geom::Position { &self.pos }
     fn radius(&self) -> f64 { self.size / 2.0 }
+    fn angle(&self) -> f64 { self.angle }
+    fn sprite(&self) -> Option<&str> { Some("player") }
 
-    fn render(&self, ctxt: &Context, gl: &mut GlGraphics) {
-        // Render the player as a little square
-        let shape = polygon::Polygon::new(color::RED);
-
-        // Rotate the player to the direction they're facing
-        let dir = 90.0;
-
-        let radius = self.radius();
-        let transform = ctxt.transform
-            .trans(self.pos.x, self.pos.y)
-            .rot_deg(dir)
-            .trans(-radius, -radius);
-
-        let points = [
-            [0.0, radius],
-            [self.size, self.size],
-            [self.size, 0.0]
-        ];
+    // The collision shape is the ship's triangle, not a circle.
+    fn collides(&self, other: &dyn GameObject) -> bool {
+        let pos = other.position();
+        geom::circle_hits_triangle([pos.x, pos.y], other.radius(), &self.hull())
+    }
 
+    fn render(&self, ctxt: &Context, gl: &mut GlGraphics) {
+        // Render the player as a triangle facing where it's heading.
+        let shape = polygon::Polygon::new(color::RED.lerp(color::WHITE, f64::from(self.flash.value())));
         shape.draw(
-            &points,
+            &self.hull(),
             &ctxt.draw_state,
-            transform,
+            ctxt.transform,
             gl
         );
+
+        // Flickering flame behind the ship.
+        let radius = self.radius();
+        let length = radius * (0.4 + 0.2 * self.thruster.index() as f64);
+        let flame = [
+            [-radius / 3.0, radius],
+            [radius / 3.0, radius],
+            [0.0, radius + length],
+        ].map(|p| self.to_world(p));
+        polygon::Polygon::new(THRUSTER_COLOR)
+            .draw(&flame, &ctxt.draw_state, ctxt.transform, gl);
+    }
+
+    fn render_dbg(&self, ctxt: &Context, gl: &mut GlGraphics) {
+        super::draw_outline(&self.hull(), ctxt, gl);
+        super::draw_velocity(&self.pos, self.velocity(), ctxt, gl);
     }
 
     fn update(&mut self, _dt: f64, size: piston::Size) {
+        self.thruster.step();
+        self.flash.step();
+        self.turn();
+
         if self.stop_movement {
             return;
         }
-        // print!("{:?} {:?} \n", (self.dir), self.pos);
 
         match self.dir {
             Direction::NORTH => self.pos.y -= PLAYER_SPEED,
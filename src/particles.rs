@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::anim::Lerp;
+
+// Most particles alive at once. Emitters stop spawning while the pool is full.
+pub const MAX_PARTICLES: usize = 2000;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Particle {
+    pub pos: [f64; 2],
+    // Distance moved per update.
+    pub vel: [f64; 2],
+    pub age: u32,
+    pub lifetime: u32,
+    pub gravity: [f64; 2],
+    pub color: ([f32; 4], [f32; 4]),
+    pub size: (f64, f64),
+}
+
+impl Particle {
+    // 0 when spawned, 1 when about to disappear.
+    pub fn life(&self) -> f64 {
+        f64::from(self.age) / f64::from(self.lifetime.max(1))
+    }
+
+    pub fn color(&self) -> [f32; 4] {
+        self.color.0.lerp(self.color.1, self.life())
+    }
+
+    pub fn size(&self) -> f64 {
+        self.size.0.lerp(self.size.1, self.life())
+    }
+}
+
+// What the particles of an emitter look like. Ranges are picked from at random.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParticleConfig {
+    pub lifetime: (u32, u32),
+    pub speed: (f64, f64),
+    // Degrees, 0 is right and 90 down the screen.
+    pub direction: f64,
+    // Particles leave within this many degrees either side of `direction`.
+    pub spread: f64,
+    pub gravity: [f64; 2],
+    // Colour and size at birth and at the end of the particle's life.
+    pub color: ([f32; 4], [f32; 4]),
+    pub size: (f64, f64),
+}
+
+impl ParticleConfig {
+    // Debris flying out in every direction.
+    pub fn explosion(color: [f32; 4]) -> ParticleConfig {
+        ParticleConfig {
+            lifetime: (20, 45),
+            speed: (0.5, 2.5),
+            direction: 0.0,
+            spread: 180.0,
+            gravity: [0.0, 0.02],
+            color: (color, [color[0], color[1], color[2], 0.0]),
+            size: (3.0, 1.0),
+        }
+    }
+
+    // Sparks where a bullet hit, thrown back down.
+    pub fn impact() -> ParticleConfig {
+        ParticleConfig {
+            lifetime: (8, 16),
+            speed: (1.0, 2.0),
+            direction: 90.0,
+            spread: 40.0,
+            gravity: [0.0, 0.05],
+            color: ([1.0, 1.0, 0.6, 1.0], [1.0, 0.3, 0.0, 0.0]),
+            size: (2.0, 1.0),
+        }
+    }
+
+    // Exhaust behind the player's ship.
+    pub fn thruster() -> ParticleConfig {
+        ParticleConfig {
+            lifetime: (10, 20),
+            speed: (1.0, 1.8),
+            direction: 90.0,
+            spread: 12.0,
+            gravity: [0.0, 0.0],
+            color: ([1.0, 0.8, 0.2, 0.9], [0.8, 0.1, 0.0, 0.0]),
+            size: (3.0, 0.5),
+        }
+    }
+
+    // Red spray where an enemy got through the defence line.
+    pub fn breach() -> ParticleConfig {
+        ParticleConfig {
+            lifetime: (25, 40),
+            speed: (1.0, 3.0),
+            direction: 270.0,
+            spread: 60.0,
+            gravity: [0.0, 0.08],
+            color: ([1.0, 0.1, 0.1, 1.0], [0.4, 0.0, 0.0, 0.0]),
+            size: (4.0, 2.0),
+        }
+    }
+}
+
+// Keeps spawning particles until removed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Emitter {
+    pub pos: [f64; 2],
+    pub config: ParticleConfig,
+    // Particles per update, fractions carry over to the next one.
+    pub rate: f64,
+    owed: f64,
+}
+
+// All particles in the world. It draws from its own generator so that
+// cosmetic particles never change what the game's generator produces.
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    emitters: BTreeMap<String, Emitter>,
+    cap: usize,
+    rng: ChaCha8Rng,
+}
+
+impl Default for ParticleSystem {
+    fn default() -> ParticleSystem {
+        ParticleSystem::new(0)
+    }
+}
+
+impl ParticleSystem {
+    pub fn new(seed: u64) -> ParticleSystem {
+        ParticleSystem::with_cap(seed, MAX_PARTICLES)
+    }
+
+    pub fn with_cap(seed: u64, cap: usize) -> ParticleSystem {
+        ParticleSystem {
+            particles: Vec::new(),
+            emitters: BTreeMap::new(),
+            cap,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn reset(&mut self, seed: u64) {
+        self.particles.clear();
+        self.emitters.clear();
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn emitter(&self, name: &str) -> Option<&Emitter> {
+        self.emitters.get(name)
+    }
+
+    // Spawn `count` particles at once.
+    pub fn burst(&mut self, pos: [f64; 2], count: u32, config: &ParticleConfig) {
+        for _ in 0..count {
+            self.spawn(pos, config);
+        }
+    }
+
+    // Add or replace a continuous emitter.
+    pub fn set_emitter(&mut self, name: &str, pos: [f64; 2], rate: f64, config: ParticleConfig) {
+        self.emitters.insert(name.to_string(), Emitter { pos, config, rate, owed: 0.0 });
+    }
+
+    pub fn move_emitter(&mut self, name: &str, pos: [f64; 2]) {
+        if let Some(emitter) = self.emitters.get_mut(name) {
+            emitter.pos = pos;
+        }
+    }
+
+    pub fn remove_emitter(&mut self, name: &str) {
+        self.emitters.remove(name);
+    }
+
+    fn spawn(&mut self, pos: [f64; 2], config: &ParticleConfig) {
+        if self.particles.len() >= self.cap {
+            return;
+        }
+
+        let angle = (config.direction + self.rng.gen_range(-1.0..=1.0) * config.spread).to_radians();
+        let speed = range(&mut self.rng, config.speed.0, config.speed.1);
+        let lifetime = self.rng.gen_range(config.lifetime.0..=config.lifetime.1.max(config.lifetime.0));
+        self.particles.push(Particle {
+            pos,
+            vel: [angle.cos() * speed, angle.sin() * speed],
+            age: 0,
+            lifetime,
+            gravity: config.gravity,
+            color: config.color,
+            size: config.size,
+        });
+    }
+
+    pub fn update(&mut self) {
+        for particle in self.particles.iter_mut() {
+            particle.age += 1;
+            particle.vel[0] += particle.gravity[0];
+            particle.vel[1] += particle.gravity[1];
+            particle.pos[0] += particle.vel[0];
+            particle.pos[1] += particle.vel[1];
+        }
+        self.particles.retain(|particle| particle.age < particle.lifetime);
+
+        let mut due = Vec::new();
+        for emitter in self.emitters.values_mut() {
+            emitter.owed += emitter.rate;
+            while emitter.owed >= 1.0 {
+                emitter.owed -= 1.0;
+                due.push((emitter.pos, emitter.config));
+            }
+        }
+        for (pos, config) in due {
+            self.spawn(pos, &config);
+        }
+    }
+}
+
+fn range(rng: &mut ChaCha8Rng, min: f64, max: f64) -> f64 {
+    if max > min { rng.gen_range(min..max) } else { min }
+}
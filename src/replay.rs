@@ -0,0 +1,160 @@
+use std::fs;
+use std::path::Path;
+
+use piston::input::Button;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::background::Background;
+use crate::save;
+use crate::world::World;
+
+const DT: f64 = 1.0 / 120.0;
+
+// One input handed to the world, before update number `tick`.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayInput {
+    pub tick: u64,
+    pub button: Button,
+    pub press: bool,
+}
+
+// Everything needed to play a session again: the world it started from and
+// every input since. Console commands aren't recorded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    // The starting world in the save format, levels and director included.
+    pub start: Value,
+    // World updates recorded.
+    pub ticks: u64,
+    pub inputs: Vec<ReplayInput>,
+    // World checksum after each update, to find where a playback drifts.
+    // Replays from before checksums have none.
+    #[serde(default)]
+    pub checksums: Vec<u64>,
+}
+
+impl Replay {
+    // Empty replay starting from `world` as it is now.
+    pub fn new(world: &World) -> Result<Replay, String> {
+        Ok(Replay { start: save::to_value(world)?, ticks: 0, inputs: Vec::new(), checksums: Vec::new() })
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read replay '{}': {}", path.display(), err))?;
+        let replay: Replay = serde_json::from_str(&text)
+            .map_err(|err| format!("Invalid replay '{}': {}", path.display(), err))?;
+        // Reject a start the game can't run before anything plays it.
+        replay.world().map_err(|err| format!("Invalid replay '{}': {}", path.display(), err))?;
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string(self).map_err(|err| err.to_string())?;
+        fs::write(path, text).map_err(|err| format!("Unable to write replay '{}': {}", path.display(), err))
+    }
+
+    // The world the recording started from.
+    pub fn world(&self) -> Result<World, String> {
+        save::from_value(self.start.clone())
+    }
+
+    // Put `world` back to the way the recording started. The highscore and
+    // background art are kept.
+    pub fn apply(&self, world: &mut World) -> Result<(), String> {
+        let mut start = self.world()?;
+        start.highscore = world.highscore;
+        start.background = Background::new(&world.background.defs(), start.seed);
+        *world = start;
+        Ok(())
+    }
+}
+
+// Builds a replay from a running game.
+pub struct Recorder {
+    pub replay: Replay,
+}
+
+impl Recorder {
+    pub fn new(world: &World) -> Result<Recorder, String> {
+        Ok(Recorder { replay: Replay::new(world)? })
+    }
+
+    pub fn input(&mut self, button: &Button, press: bool) {
+        self.replay.inputs.push(ReplayInput { tick: self.replay.ticks, button: *button, press });
+    }
+
+    // Call after every world update.
+    pub fn tick(&mut self, world: &World) {
+        self.replay.ticks += 1;
+        self.replay.checksums.push(world.checksum());
+    }
+}
+
+// Feeds a replay's inputs into a world update by update.
+pub struct Playback {
+    pub replay: Replay,
+    tick: u64,
+    next: usize,
+    // First tick whose checksum didn't match the recording.
+    pub diverged: Option<u64>,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Playback {
+        Playback { replay, tick: 0, next: 0, diverged: None }
+    }
+
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn finished(&self) -> bool {
+        self.tick >= self.replay.ticks
+    }
+
+    // Hand over this tick's inputs and update the world once.
+    pub fn step(&mut self, world: &mut World) {
+        while let Some(input) = self.replay.inputs.get(self.next) {
+            if input.tick > self.tick {
+                break;
+            }
+            world.input(&input.button, input.press);
+            self.next += 1;
+        }
+        world.update(DT);
+        if self.diverged.is_none() {
+            let recorded = self.replay.checksums.get(self.tick as usize);
+            if recorded.is_some_and(|checksum| *checksum != world.checksum()) {
+                self.diverged = Some(self.tick);
+            }
+        }
+        self.tick += 1;
+    }
+}
+
+// Play a whole replay without a window and return the world it ends in.
+pub fn run(replay: &Replay) -> Result<World, String> {
+    play(replay).map(|(world, _)| world)
+}
+
+// Play a whole replay and check every update against the recorded checksums.
+pub fn verify(replay: &Replay) -> Result<World, String> {
+    if replay.checksums.is_empty() && replay.ticks > 0 {
+        return Err("Replay has no checksums to verify against".to_string());
+    }
+    match play(replay)? {
+        (world, None) => Ok(world),
+        (_, Some(tick)) => Err(format!("Replay diverges at tick {}", tick)),
+    }
+}
+
+fn play(replay: &Replay) -> Result<(World, Option<u64>), String> {
+    let mut world = replay.world()?;
+    let mut playback = Playback::new(replay.clone());
+    while !playback.finished() {
+        playback.step(&mut world);
+    }
+    Ok((world, playback.diverged))
+}
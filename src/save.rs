@@ -0,0 +1,151 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::world::World;
+
+pub const SAVE_DIR: &str = "data/saves";
+pub const SLOTS: usize = 3;
+
+// Turns a save of one version into the next one.
+pub type Migration = fn(Value) -> Result<Value, String>;
+
+// MIGRATIONS[n] upgrades a version n + 1 save to version n + 2. Changing the
+// saved world in a way old saves can't be read with needs a migration here,
+// which also bumps the version.
+const MIGRATIONS: &[Migration] = &[];
+pub const SAVE_VERSION: u64 = MIGRATIONS.len() as u64 + 1;
+
+#[derive(Serialize)]
+struct SaveFile<'a> {
+    version: u64,
+    world: &'a World,
+}
+
+pub fn to_json(world: &World) -> Result<String, String> {
+    serde_json::to_string_pretty(&SaveFile { version: SAVE_VERSION, world })
+        .map_err(|err| format!("Unable to save the world: {}", err))
+}
+
+// A save as a JSON value, for embedding in other files.
+pub fn to_value(world: &World) -> Result<Value, String> {
+    serde_json::to_value(SaveFile { version: SAVE_VERSION, world })
+        .map_err(|err| format!("Unable to save the world: {}", err))
+}
+
+// Read a save of any known version. The world's effects start over.
+pub fn from_json(text: &str) -> Result<World, String> {
+    let value: Value = serde_json::from_str(text).map_err(|err| format!("Invalid save: {}", err))?;
+    from_value(value)
+}
+
+pub fn from_value(value: Value) -> Result<World, String> {
+    let value = upgrade(value, MIGRATIONS)?;
+    let world = value.get("world").cloned().ok_or("Save without a world")?;
+    let mut world: World = serde_json::from_value(world).map_err(|err| format!("Invalid save: {}", err))?;
+    world.rules.validate().map_err(|err| format!("Invalid save: {}", err))?;
+    world.restart_effects();
+    Ok(world)
+}
+
+// Bring a save up to the latest version `migrations` lead to.
+pub fn upgrade(mut value: Value, migrations: &[Migration]) -> Result<Value, String> {
+    let latest = migrations.len() as u64 + 1;
+    let mut version = value.get("version").and_then(Value::as_u64).ok_or("Save without a version")?;
+    if version == 0 || version > latest {
+        return Err(format!("Save version {} is not supported, this game reads up to version {}", version, latest));
+    }
+    while version < latest {
+        value = migrations[version as usize - 1](value)
+            .map_err(|err| format!("Unable to upgrade save from version {}: {}", version, err))?;
+        version += 1;
+        value["version"] = Value::from(version);
+    }
+    Ok(value)
+}
+
+// Numbered save files in one folder.
+pub struct Slots {
+    dir: PathBuf,
+}
+
+impl Default for Slots {
+    fn default() -> Slots {
+        Slots::new(SAVE_DIR)
+    }
+}
+
+impl Slots {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Slots {
+        Slots { dir: dir.as_ref().to_path_buf() }
+    }
+
+    pub fn path(&self, slot: usize) -> PathBuf {
+        self.dir.join(format!("slot_{}.json", slot))
+    }
+
+    pub fn exists(&self, slot: usize) -> bool {
+        self.path(slot).is_file()
+    }
+
+    pub fn save(&self, slot: usize, world: &World) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|err| format!("Unable to create '{}': {}", self.dir.display(), err))?;
+        let path = self.path(slot);
+        fs::write(&path, to_json(world)?).map_err(|err| format!("Unable to write '{}': {}", path.display(), err))
+    }
+
+    pub fn load(&self, slot: usize) -> Result<World, String> {
+        let path = self.path(slot);
+        let text = fs::read_to_string(&path).map_err(|err| format!("Unable to read '{}': {}", path.display(), err))?;
+        from_json(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn delete(&self, slot: usize) -> Result<(), String> {
+        let path = self.path(slot);
+        fs::remove_file(&path).map_err(|err| format!("Unable to delete '{}': {}", path.display(), err))
+    }
+}
+
+// Playfield size as [width, height].
+pub(crate) mod size_format {
+    use piston::window::Size;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(size: &Size, serializer: S) -> Result<S::Ok, S::Error> {
+        [size.width, size.height].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Size, D::Error> {
+        let [width, height] = <[f64; 2]>::deserialize(deserializer)?;
+        Ok(Size { width, height })
+    }
+}
+
+// The generator's seed and how far into its stream it is, enough to carry on
+// with exactly the same numbers.
+pub(crate) mod rng_format {
+    use rand_chacha::ChaCha8Rng;
+    use rand::SeedableRng;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct RngState {
+        seed: [u8; 32],
+        stream: u64,
+        word_pos: u128,
+    }
+
+    pub fn serialize<S: Serializer>(rng: &ChaCha8Rng, serializer: S) -> Result<S::Ok, S::Error> {
+        RngState { seed: rng.get_seed(), stream: rng.get_stream(), word_pos: rng.get_word_pos() }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ChaCha8Rng, D::Error> {
+        let state = RngState::deserialize(deserializer)?;
+        let mut rng = ChaCha8Rng::from_seed(state.seed);
+        rng.set_stream(state.stream);
+        rng.set_word_pos(state.word_pos);
+        Ok(rng)
+    }
+}
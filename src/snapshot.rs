@@ -0,0 +1,75 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::save::{self, SAVE_VERSION};
+use crate::world::World;
+
+pub const SNAPSHOT_DIR: &str = "data/snapshots";
+
+// A save with a note on why it was taken, so it loads like one.
+#[derive(Serialize)]
+struct SnapshotFile<'a> {
+    version: u64,
+    reason: &'a str,
+    world: &'a World,
+}
+
+// The whole world as pretty JSON: every entity with all its fields, the game
+// status, counters and the generator state.
+pub fn to_json(world: &World, reason: &str) -> Result<String, String> {
+    serde_json::to_string_pretty(&SnapshotFile { version: SAVE_VERSION, reason, world })
+        .map_err(|err| format!("Unable to snapshot the world: {}", err))
+}
+
+// Restore a snapshot, or a save, into a world that runs without a window.
+pub fn load(path: &Path) -> Result<World, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| format!("Unable to read snapshot '{}': {}", path.display(), err))?;
+    save::from_json(&text).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+// Writes snapshots into a folder, on request and every `every` updates.
+pub struct Snapshots {
+    dir: PathBuf,
+    pub every: Option<u64>,
+    // Update the last periodic snapshot was taken on, the world stops
+    // counting once a game is over.
+    last: Option<u64>,
+}
+
+impl Default for Snapshots {
+    fn default() -> Snapshots {
+        Snapshots::new(SNAPSHOT_DIR, None)
+    }
+}
+
+impl Snapshots {
+    pub fn new<P: AsRef<Path>>(dir: P, every: Option<u64>) -> Snapshots {
+        Snapshots { dir: dir.as_ref().to_path_buf(), every, last: None }
+    }
+
+    pub fn path(&self, world: &World, reason: &str) -> PathBuf {
+        self.dir.join(format!("snapshot_{}_{}.json", world.uptime, reason))
+    }
+
+    pub fn take(&self, world: &World, reason: &str) -> Result<PathBuf, String> {
+        fs::create_dir_all(&self.dir).map_err(|err| format!("Unable to create '{}': {}", self.dir.display(), err))?;
+        let path = self.path(world, reason);
+        fs::write(&path, to_json(world, reason)?)
+            .map_err(|err| format!("Unable to write snapshot '{}': {}", path.display(), err))?;
+        Ok(path)
+    }
+
+    // Call after every update, takes the periodic snapshots.
+    pub fn tick(&mut self, world: &World) -> Result<Option<PathBuf>, String> {
+        match self.every {
+            Some(every) if every > 0 && world.uptime.is_multiple_of(every) && self.last != Some(world.uptime) => {
+                self.last = Some(world.uptime);
+                self.take(world, "tick").map(Some)
+            },
+            _ => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use piston::window::Size;
+use serde::{Deserialize, Serialize};
+
+// Side of the playfield an enemy enters from. It travels straight across to
+// the opposite side.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Edge {
+    #[default]
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Edge {
+    pub const ALL: [Edge; 4] = [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right];
+
+    // Unit vector pointing into the playfield.
+    pub fn heading(self) -> [f64; 2] {
+        match self {
+            Edge::Top => [0.0, 1.0],
+            Edge::Bottom => [0.0, -1.0],
+            Edge::Left => [1.0, 0.0],
+            Edge::Right => [-1.0, 0.0],
+        }
+    }
+
+    // Point on this edge, `t` going from 0 to 1 along it.
+    fn point(self, t: f64, size: Size) -> [f64; 2] {
+        match self {
+            Edge::Top => [t * size.width, 0.0],
+            Edge::Bottom => [t * size.width, size.height],
+            Edge::Left => [0.0, t * size.height],
+            Edge::Right => [size.width, t * size.height],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub x: f64,
+    pub y: f64,
+    pub from: Edge,
+}
+
+// A point given relative to the playfield, so it survives resizes.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RelativePoint {
+    // 0 is the left/top border, 1 the right/bottom one.
+    pub x: f64,
+    pub y: f64,
+    #[serde(default)]
+    pub from: Edge,
+}
+
+// Where enemies may appear.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpawnLayout {
+    // `count` evenly spaced lanes along the top edge.
+    Lanes { count: usize },
+    // Hand placed points.
+    Points { points: Vec<RelativePoint> },
+    // `per_side` evenly spaced points along each of `sides`.
+    Edges { sides: Vec<Edge>, per_side: usize },
+}
+
+impl Default for SpawnLayout {
+    // Nine lanes, the original layout.
+    fn default() -> SpawnLayout {
+        SpawnLayout::Lanes { count: 9 }
+    }
+}
+
+impl SpawnLayout {
+    // Parse the short command line form: `lanes:N`, `edges:N` or
+    // `edges:N:top,left,...`.
+    pub fn parse(spec: &str) -> Result<SpawnLayout, String> {
+        let mut parts = spec.split(':');
+        let kind = parts.next().unwrap_or_default();
+        let count = parts.next()
+            .ok_or_else(|| format!("Missing count in spawn layout '{}'", spec))?
+            .parse::<usize>()
+            .map_err(|_| format!("Invalid count in spawn layout '{}'", spec))?;
+
+        let layout = match kind {
+            "lanes" => SpawnLayout::Lanes { count },
+            "edges" => {
+                let sides = match parts.next() {
+                    None => Edge::ALL.to_vec(),
+                    Some(sides) => sides.split(',')
+                        .map(|side| serde_json::from_value(serde_json::Value::String(side.to_string()))
+                            .map_err(|_| format!("Unknown edge '{}'", side)))
+                        .collect::<Result<Vec<Edge>, String>>()?,
+                };
+                SpawnLayout::Edges { sides, per_side: count }
+            },
+            _ => return Err(format!("Unknown spawn layout '{}', expected lanes:N or edges:N", kind)),
+        };
+
+        if layout.is_empty() {
+            return Err(format!("Spawn layout '{}' has no spawn points", spec));
+        }
+        Ok(layout)
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            SpawnLayout::Lanes { count } => *count == 0,
+            SpawnLayout::Points { points } => points.is_empty(),
+            SpawnLayout::Edges { sides, per_side } => sides.is_empty() || *per_side == 0,
+        }
+    }
+
+    // Absolute spawn points for a playfield of the given size.
+    pub fn points(&self, size: Size) -> Vec<SpawnPoint> {
+        match self {
+            SpawnLayout::Lanes { count } => spread(Edge::Top, *count, size),
+            SpawnLayout::Points { points } => points.iter().map(|point| SpawnPoint {
+                x: point.x.clamp(0.0, 1.0) * size.width,
+                y: point.y.clamp(0.0, 1.0) * size.height,
+                from: point.from,
+            }).collect(),
+            SpawnLayout::Edges { sides, per_side } => sides.iter()
+                .flat_map(|side| spread(*side, *per_side, size))
+                .collect(),
+        }
+    }
+}
+
+// `count` points along `edge`, keeping the corners free.
+fn spread(edge: Edge, count: usize, size: Size) -> Vec<SpawnPoint> {
+    (1..=count).map(|i| {
+        let [x, y] = edge.point(i as f64 / (count + 1) as f64, size);
+        SpawnPoint { x, y, from: edge }
+    }).collect()
+}
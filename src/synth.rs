@@ -0,0 +1,231 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::audio::{Sfx, Sound, SAMPLE_RATE};
+
+// Seed the game's sound effects are generated from unless told otherwise.
+pub const DEFAULT_SEED: u64 = 7;
+// Noise changes value this many times per wave period.
+const NOISE_STEPS: f64 = 32.0;
+// Nothing is allowed to ring on for longer than this, in seconds.
+const MAX_LENGTH: f64 = 4.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Wave {
+    Square,
+    Saw,
+    Noise,
+}
+
+// Kinds of sound the generator knows how to vary, after sfxr's buttons.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Preset {
+    Shoot,
+    Explosion,
+    Pickup,
+    Hurt,
+    // Long falling tone, for losing.
+    PowerDown,
+}
+
+impl Preset {
+    pub fn for_sfx(sfx: Sfx) -> Preset {
+        match sfx {
+            Sfx::Shoot => Preset::Shoot,
+            Sfx::Hit => Preset::Hurt,
+            Sfx::Explosion | Sfx::Breach => Preset::Explosion,
+            Sfx::Pickup => Preset::Pickup,
+            Sfx::GameOver => Preset::PowerDown,
+        }
+    }
+}
+
+// Everything that describes one sound. Frequencies are in Hz, times in
+// seconds and slides in octaves per second.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SynthParams {
+    pub wave: Wave,
+    pub freq: f64,
+    // The sound stops once a falling slide gets below this.
+    pub min_freq: f64,
+    pub slide: f64,
+    // Change of `slide` per second.
+    pub delta_slide: f64,
+    // Share of a square wave's period spent high, and its change per second.
+    pub duty: f64,
+    pub duty_sweep: f64,
+    // Multiply the frequency by `arp_mult` once `arp_time` has passed, 0 never.
+    pub arp_mult: f64,
+    pub arp_time: f64,
+    pub attack: f64,
+    pub sustain: f64,
+    // Extra loudness at the start of the sustain, fading over it.
+    pub punch: f64,
+    pub decay: f64,
+    pub volume: f32,
+}
+
+impl Default for SynthParams {
+    fn default() -> SynthParams {
+        SynthParams {
+            wave: Wave::Square,
+            freq: 440.0,
+            min_freq: 20.0,
+            slide: 0.0,
+            delta_slide: 0.0,
+            duty: 0.5,
+            duty_sweep: 0.0,
+            arp_mult: 1.0,
+            arp_time: 0.0,
+            attack: 0.0,
+            sustain: 0.1,
+            punch: 0.0,
+            decay: 0.2,
+            volume: 0.5,
+        }
+    }
+}
+
+impl SynthParams {
+    // Random parameters in the style of `preset`, the same for the same seed.
+    pub fn preset(preset: Preset, seed: u64) -> SynthParams {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let rng = &mut rng;
+        match preset {
+            Preset::Shoot => SynthParams {
+                wave: if rng.gen_bool(0.5) { Wave::Square } else { Wave::Saw },
+                freq: rng.gen_range(500.0..1200.0),
+                min_freq: 100.0,
+                slide: -rng.gen_range(8.0..20.0),
+                duty: rng.gen_range(0.2..0.5),
+                duty_sweep: rng.gen_range(0.0..2.0),
+                sustain: rng.gen_range(0.02..0.08),
+                decay: rng.gen_range(0.05..0.15),
+                punch: rng.gen_range(0.0..0.5),
+                volume: 0.4,
+                ..SynthParams::default()
+            },
+            Preset::Explosion => SynthParams {
+                wave: Wave::Noise,
+                freq: rng.gen_range(60.0..300.0),
+                slide: -rng.gen_range(0.5..2.0),
+                sustain: rng.gen_range(0.05..0.2),
+                punch: rng.gen_range(0.3..0.8),
+                decay: rng.gen_range(0.3..0.6),
+                volume: 0.6,
+                ..SynthParams::default()
+            },
+            Preset::Pickup => SynthParams {
+                wave: Wave::Square,
+                freq: rng.gen_range(600.0..1400.0),
+                duty: rng.gen_range(0.3..0.5),
+                arp_mult: rng.gen_range(1.3..2.0),
+                arp_time: rng.gen_range(0.04..0.08),
+                sustain: rng.gen_range(0.05..0.12),
+                punch: rng.gen_range(0.2..0.5),
+                decay: rng.gen_range(0.1..0.25),
+                volume: 0.4,
+                ..SynthParams::default()
+            },
+            Preset::Hurt => SynthParams {
+                wave: if rng.gen_bool(0.5) { Wave::Saw } else { Wave::Noise },
+                freq: rng.gen_range(200.0..600.0),
+                slide: -rng.gen_range(3.0..8.0),
+                sustain: rng.gen_range(0.02..0.06),
+                decay: rng.gen_range(0.08..0.2),
+                volume: 0.5,
+                ..SynthParams::default()
+            },
+            Preset::PowerDown => SynthParams {
+                wave: Wave::Saw,
+                freq: rng.gen_range(400.0..700.0),
+                slide: -rng.gen_range(0.8..1.5),
+                delta_slide: -rng.gen_range(0.0..0.5),
+                sustain: rng.gen_range(0.4..0.7),
+                decay: rng.gen_range(0.5..0.9),
+                volume: 0.5,
+                ..SynthParams::default()
+            },
+        }
+    }
+
+    pub fn length(&self) -> f64 {
+        (self.attack + self.sustain + self.decay).clamp(0.0, MAX_LENGTH)
+    }
+
+    // Volume of the envelope at time `t`.
+    fn envelope(&self, t: f64) -> f64 {
+        if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.sustain {
+            let into = (t - self.attack) / self.sustain;
+            1.0 + self.punch * (1.0 - into)
+        } else if self.decay > 0.0 {
+            (1.0 - (t - self.attack - self.sustain) / self.decay).max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    // Render the sound. Noise is drawn from `seed`, so the result is the same
+    // every time.
+    pub fn render(&self, seed: u64) -> Sound {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let dt = 1.0 / f64::from(SAMPLE_RATE);
+        let count = (self.length() * f64::from(SAMPLE_RATE)) as usize;
+
+        let mut samples = Vec::with_capacity(count);
+        let mut freq = self.freq;
+        let mut slide = self.slide;
+        let mut duty = self.duty;
+        let mut arp_done = self.arp_time <= 0.0;
+        let mut phase = 0.0;
+        let mut noise = rng.gen_range(-1.0..1.0);
+        for i in 0..count {
+            let t = i as f64 * dt;
+            if !arp_done && t >= self.arp_time {
+                freq *= self.arp_mult;
+                arp_done = true;
+            }
+            slide += self.delta_slide * dt;
+            freq *= (slide * dt).exp2();
+            if freq < self.min_freq && slide < 0.0 {
+                break;
+            }
+            duty = (duty + self.duty_sweep * dt).clamp(0.05, 0.95);
+
+            let last_step = (phase * NOISE_STEPS) as u32;
+            phase = (phase + freq * dt).fract();
+            if (phase * NOISE_STEPS) as u32 != last_step {
+                noise = rng.gen_range(-1.0..1.0);
+            }
+
+            let value = match self.wave {
+                Wave::Square => if phase < duty { 1.0 } else { -1.0 },
+                Wave::Saw => 1.0 - 2.0 * phase,
+                Wave::Noise => noise,
+            };
+            let sample = value * self.envelope(t) * f64::from(self.volume);
+            samples.push(sample.clamp(-1.0, 1.0) as f32);
+        }
+
+        Sound { rate: SAMPLE_RATE, samples: samples.into() }
+    }
+}
+
+// The game's default sound for `sfx`. Every effect gets its own seed, so
+// effects sharing a preset still sound different.
+pub fn sfx_sound(sfx: Sfx, seed: u64) -> Sound {
+    let index = Sfx::ALL.iter().position(|other| *other == sfx).unwrap_or(0) as u64;
+    let seed = seed.wrapping_mul(Sfx::ALL.len() as u64).wrapping_add(index);
+    let mut params = SynthParams::preset(Preset::for_sfx(sfx), seed);
+    if sfx == Sfx::Breach {
+        // Deeper and longer than an enemy blowing up.
+        params.freq /= 2.0;
+        params.decay *= 1.5;
+    }
+    params.render(seed)
+}
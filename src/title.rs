@@ -0,0 +1,90 @@
+use piston::input::Key;
+
+use crate::save::{Slots, SLOTS};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MenuItem {
+    Resume,
+    NewGame,
+    Continue,
+    SaveAndQuit,
+    Quit,
+}
+
+// Title screen, also shown as the pause menu while a game is running. Up and
+// Down pick an entry, Left and Right the save slot.
+pub struct TitleScreen {
+    pub selected: usize,
+    pub slot: usize,
+    // A game is running that can be resumed or saved.
+    pub in_game: bool,
+    // Which slots hold a save.
+    pub used: [bool; SLOTS],
+    // Shown under the menu, e.g. why a save failed.
+    pub message: Option<String>,
+}
+
+impl TitleScreen {
+    pub fn new(in_game: bool, slots: &Slots) -> TitleScreen {
+        let mut used = [false; SLOTS];
+        for (slot, used) in used.iter_mut().enumerate() {
+            *used = slots.exists(slot);
+        }
+        // Start on the first slot with a save to continue from.
+        let slot = used.iter().position(|used| *used).unwrap_or(0);
+        TitleScreen { selected: 0, slot, in_game, used, message: None }
+    }
+
+    pub fn items(&self) -> Vec<MenuItem> {
+        let mut items = Vec::new();
+        if self.in_game {
+            items.push(MenuItem::Resume);
+        }
+        items.extend([MenuItem::NewGame, MenuItem::Continue]);
+        if self.in_game {
+            items.push(MenuItem::SaveAndQuit);
+        }
+        items.push(MenuItem::Quit);
+        items
+    }
+
+    pub fn label(&self, item: MenuItem) -> String {
+        match item {
+            MenuItem::Resume => "Resume".to_string(),
+            MenuItem::NewGame => "New game".to_string(),
+            MenuItem::Continue if self.used[self.slot] => format!("Continue slot {}", self.slot + 1),
+            MenuItem::Continue => format!("Continue slot {} (empty)", self.slot + 1),
+            MenuItem::SaveAndQuit => format!("Save to slot {} and quit", self.slot + 1),
+            MenuItem::Quit => "Quit".to_string(),
+        }
+    }
+
+    // Menu text, the selected entry marked.
+    pub fn lines(&self) -> Vec<String> {
+        self.items().into_iter().enumerate().map(|(i, item)| {
+            let marker = if i == self.selected { ">" } else { " " };
+            format!("{} {}", marker, self.label(item))
+        }).collect()
+    }
+
+    // The entry chosen with this key press, if any.
+    pub fn input(&mut self, key: Key) -> Option<MenuItem> {
+        let count = self.items().len();
+        match key {
+            Key::Up => self.selected = (self.selected + count - 1) % count,
+            Key::Down => self.selected = (self.selected + 1) % count,
+            Key::Left => self.slot = (self.slot + SLOTS - 1) % SLOTS,
+            Key::Right => self.slot = (self.slot + 1) % SLOTS,
+            Key::Return => {
+                let item = self.items()[self.selected];
+                if item == MenuItem::Continue && !self.used[self.slot] {
+                    self.message = Some(format!("Slot {} is empty", self.slot + 1));
+                    return None;
+                }
+                return Some(item);
+            },
+            _ => (),
+        }
+        None
+    }
+}
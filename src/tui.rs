@@ -0,0 +1,195 @@
+use std::fmt::Write;
+
+use crate::models::GameObject;
+use crate::models::pickup::PickupKind;
+use crate::world::{GameStatus, World};
+
+// Colours available to the terminal frontend, mapped to ANSI SGR codes.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Color {
+    Default,
+    White,
+    Red,
+    Green,
+    Cyan,
+}
+
+impl Color {
+    fn ansi_code(self) -> u8 {
+        match self {
+            Color::Default => 39,
+            Color::White => 97,
+            Color::Red => 91,
+            Color::Green => 92,
+            Color::Cyan => 96,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    pub color: Color,
+}
+
+impl Cell {
+    pub const EMPTY: Cell = Cell { ch: ' ', color: Color::Default };
+}
+
+// A grid of characters the world is drawn into. The top and bottom rows are
+// reserved for the HUD, the rows in between hold the scaled playfield.
+pub struct Screen {
+    pub cols: usize,
+    pub rows: usize,
+    cells: Vec<Cell>,
+}
+
+impl Screen {
+    pub fn new(cols: usize, rows: usize) -> Screen {
+        Screen {
+            cols,
+            rows,
+            cells: vec![Cell::EMPTY; cols * rows],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|cell| *cell = Cell::EMPTY);
+    }
+
+    pub fn get(&self, col: usize, row: usize) -> Option<Cell> {
+        if col < self.cols && row < self.rows {
+            Some(self.cells[row * self.cols + col])
+        } else {
+            None
+        }
+    }
+
+    pub fn put(&mut self, col: usize, row: usize, ch: char, color: Color) {
+        if col < self.cols && row < self.rows {
+            self.cells[row * self.cols + col] = Cell { ch, color };
+        }
+    }
+
+    pub fn text(&mut self, col: usize, row: usize, txt: &str, color: Color) {
+        for (i, ch) in txt.chars().enumerate() {
+            self.put(col + i, row, ch, color);
+        }
+    }
+
+    // Draw text centred on the given row.
+    pub fn text_center(&mut self, row: usize, txt: &str, color: Color) {
+        let len = txt.chars().count();
+        let col = self.cols.saturating_sub(len) / 2;
+        self.text(col, row, txt, color);
+    }
+
+    fn field_rows(&self) -> usize {
+        self.rows.saturating_sub(2)
+    }
+
+    // Map a position in world units to a cell of the playfield.
+    pub fn to_cell(&self, world: &World, x: f64, y: f64) -> Option<(usize, usize)> {
+        let field_rows = self.field_rows();
+        if self.cols == 0 || field_rows == 0 || x < 0.0 || y < 0.0 {
+            return None;
+        }
+
+        let col = (x / world.size.width * self.cols as f64) as usize;
+        let row = (y / world.size.height * field_rows as f64) as usize;
+        if col < self.cols && row < field_rows {
+            Some((col, row + 1))
+        } else {
+            None
+        }
+    }
+
+    fn put_object(&mut self, world: &World, object: &dyn GameObject, ch: char, color: Color) {
+        let pos = object.position();
+        if let Some((col, row)) = self.to_cell(world, pos.x, pos.y) {
+            self.put(col, row, ch, color);
+        }
+    }
+
+    pub fn draw_world(&mut self, world: &World) {
+        self.clear();
+
+        match world.game_status {
+            GameStatus::Normal => {
+                for enemy in world.enemies.iter() {
+                    self.put_object(world, enemy, '#', Color::White);
+                }
+
+                for bullet in world.bullets.iter() {
+                    self.put_object(world, bullet, '|', Color::Red);
+                }
+
+                for pickup in world.pickups.iter() {
+                    let color = match pickup.kind {
+                        PickupKind::Ammo => Color::Cyan,
+                        PickupKind::Health => Color::Green,
+                    };
+                    self.put_object(world, pickup, '+', color);
+                }
+
+                for effect in world.effects.iter() {
+                    self.put_object(world, effect, '*', Color::Red);
+                }
+
+                self.put_object(world, &world.player, 'A', Color::Red);
+
+                let score = format!("Score: {:?}", world.score);
+                self.text(0, 0, &score, Color::White);
+
+                let health = format!("Health: {:?}", world.player.health);
+                let col = self.cols.saturating_sub(health.len());
+                self.text(col, 0, &health, Color::White);
+
+                if let Some(level) = world.campaign.as_ref().and_then(|campaign| campaign.level()) {
+                    self.text(0, self.rows.saturating_sub(1), &level.name, Color::White);
+                }
+
+                let ammo = format!("Bullets: {:?}", world.ammo);
+                let col = self.cols.saturating_sub(ammo.len());
+                self.text(col, self.rows.saturating_sub(1), &ammo, Color::White);
+            },
+            GameStatus::Died | GameStatus::Win => {
+                let (color, state) = if world.game_status == GameStatus::Died {
+                    (Color::Red, "DEAD")
+                } else {
+                    (Color::Green, "WIN")
+                };
+
+                let middle = self.rows / 2;
+                self.text_center(middle.saturating_sub(2), state, color);
+                self.text_center(middle, &format!("Score: {:?}", world.score), Color::White);
+                self.text_center(middle + 1, &format!("Highscore: {:?}", world.highscore), Color::White);
+                self.text_center(middle + 3, "Press Enter to restart", Color::Cyan);
+            }
+        }
+    }
+
+    // Plain text of a single row, without colours.
+    pub fn row_text(&self, row: usize) -> String {
+        self.cells[row * self.cols..(row + 1) * self.cols].iter().map(|cell| cell.ch).collect()
+    }
+
+    // The whole screen as ANSI escape sequences, starting from the top left
+    // corner so successive frames overwrite each other.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::with_capacity(self.cells.len() * 2);
+        let mut current = None;
+        for row in 0..self.rows {
+            let _ = write!(out, "\x1b[{};1H", row + 1);
+            for cell in &self.cells[row * self.cols..(row + 1) * self.cols] {
+                if current != Some(cell.color) {
+                    let _ = write!(out, "\x1b[{}m", cell.color.ansi_code());
+                    current = Some(cell.color);
+                }
+                out.push(cell.ch);
+            }
+        }
+        out.push_str("\x1b[0m");
+        out
+    }
+}
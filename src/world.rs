@@ -0,0 +1,602 @@
+use piston::input::{Button, Key};
+use piston::window::Size;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::background::Background;
+use crate::checksum::Checksum;
+use crate::geom::{self, Direction};
+use crate::director::Director;
+use crate::level::Campaign;
+use crate::particles::{ParticleConfig, ParticleSystem};
+use crate::spawn::{SpawnLayout, SpawnPoint};
+use crate::models::GameObject;
+use crate::models::bullet::Bullet;
+use crate::models::effect::Effect;
+use crate::models::enemy::{Enemy, EnemyKind, Movement};
+use crate::models::pickup::{Pickup, PickupKind, PICKUP_AMMO, PICKUP_HEALTH};
+use crate::models::player::{Player, PLAYER_HEALTH};
+
+pub const SHOOT_COOLDOWN: u32 = 50;
+pub const ENEMY_SPAWN_COOLDOWN: u64 = 500;
+pub const START_AMMO: u32 = 20;
+// Upper bounds used by `World::check_invariants` to catch runaway entity counts.
+pub const MAX_ENEMIES: usize = 1000;
+pub const MAX_BULLETS: usize = 1000;
+
+// Particles thrown out by the different events.
+const EXPLOSION_PARTICLES: u32 = 24;
+const IMPACT_PARTICLES: u32 = 6;
+const BREACH_PARTICLES: u32 = 20;
+// Name and particles per update of the player's exhaust emitter.
+const THRUSTER: &str = "thruster";
+const THRUSTER_RATE: f64 = 0.5;
+
+// Things that happened during the last update, for frontends to react to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    // An enemy got through, costing the player `damage` health.
+    Breach { x: f64, y: f64, damage: f64 },
+    Kill { kind: EnemyKind, x: f64, y: f64 },
+    Shot,
+    Pickup { kind: PickupKind },
+    GameOver { won: bool },
+}
+
+// Tunable gameplay values. The defaults are the original hard coded ones.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules {
+    // Updates before the player can shoot after a (re)start.
+    pub shoot_cooldown: u32,
+    // Updates between enemy waves at difficulty zero, also the difficulty needed to win.
+    pub enemy_spawn_cooldown: u64,
+    // Shortest possible interval between enemy waves.
+    pub min_spawn_interval: u64,
+    // Updates per difficulty step.
+    pub difficulty_interval: u64,
+    // Chance of every column to spawn an enemy in a wave.
+    pub spawn_chance: f32,
+    pub start_ammo: u32,
+    pub ammo_per_kill: u32,
+}
+
+impl Default for Rules {
+    fn default() -> Rules {
+        Rules {
+            shoot_cooldown: SHOOT_COOLDOWN,
+            enemy_spawn_cooldown: ENEMY_SPAWN_COOLDOWN,
+            min_spawn_interval: 100,
+            difficulty_interval: 50,
+            spawn_chance: 0.3,
+            start_ammo: START_AMMO,
+            ammo_per_kill: 2,
+        }
+    }
+}
+
+impl Rules {
+    pub const NAMES: [&'static str; 7] = [
+        "shoot_cooldown",
+        "enemy_spawn_cooldown",
+        "min_spawn_interval",
+        "difficulty_interval",
+        "spawn_chance",
+        "start_ammo",
+        "ammo_per_kill",
+    ];
+
+    // Set a rule by name, as used for command line overrides and sweeps. Call
+    // `validate` once every override is in.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+            value.parse::<T>().map_err(|_| format!("Invalid value '{}' for rule '{}'", value, name))
+        }
+
+        match name {
+            "shoot_cooldown" => self.shoot_cooldown = parse(name, value)?,
+            "enemy_spawn_cooldown" => self.enemy_spawn_cooldown = parse(name, value)?,
+            "min_spawn_interval" => self.min_spawn_interval = parse(name, value)?,
+            "difficulty_interval" => self.difficulty_interval = parse(name, value)?,
+            "spawn_chance" => self.spawn_chance = parse(name, value)?,
+            "start_ammo" => self.start_ammo = parse(name, value)?,
+            "ammo_per_kill" => self.ammo_per_kill = parse(name, value)?,
+            _ => return Err(format!("Unknown rule '{}', expected one of {}", name, Rules::NAMES.join(", "))),
+        }
+        Ok(())
+    }
+
+    // Rules the simulation can run with: the spawn interval shrinks from
+    // `enemy_spawn_cooldown` down to `min_spawn_interval` and is never zero.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.difficulty_interval == 0 {
+            return Err("Rule 'difficulty_interval' must be at least 1".to_string());
+        }
+        if self.min_spawn_interval == 0 {
+            return Err("Rule 'min_spawn_interval' must be at least 1".to_string());
+        }
+        if self.min_spawn_interval > self.enemy_spawn_cooldown {
+            return Err(format!("Rule 'min_spawn_interval' = {} is above 'enemy_spawn_cooldown' = {}",
+                               self.min_spawn_interval, self.enemy_spawn_cooldown));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameStatus {
+    Normal,
+    Win,
+    Died
+}
+
+// The game simulation without any window or OpenGL state, so it can be
+// driven by any frontend (or none at all). Serialising it gives the full
+// game state, leaving out what is only for show.
+#[derive(Serialize, Deserialize)]
+pub struct World {
+    #[serde(with = "crate::save::size_format")]
+    pub size: Size,
+    pub rules: Rules,
+    pub player: Player,
+    pub game_status: GameStatus,
+    pub enemies: Vec<Enemy>,
+    pub bullets: Vec<Bullet>,
+    pub pickups: Vec<Pickup>,
+    // Explosions and the like, only for show.
+    #[serde(skip)]
+    pub effects: Vec<Effect>,
+    #[serde(skip)]
+    pub particles: ParticleSystem,
+    #[serde(skip)]
+    pub background: Background,
+    // Cleared at the start of every update.
+    #[serde(skip)]
+    pub events: Vec<GameEvent>,
+    // Where enemies may appear, and the points that gives on the current playfield.
+    pub spawn_layout: SpawnLayout,
+    pub spawn_points: Vec<SpawnPoint>,
+    pub fire_bullet: bool,
+    pub score: u32,
+    pub ammo: u32,
+    pub highscore: u32,
+    pub shoot_cooldown: u32,
+    pub uptime: u64,
+    // Number of enemies that reached the bottom of the screen this game.
+    pub breaches: u32,
+    // Bullets fired this game.
+    pub shots: u32,
+    // Seed the current game was started from and the generator derived from it.
+    pub seed: u64,
+    #[serde(with = "crate::save::rng_format")]
+    pub rng: ChaCha8Rng,
+    // Scripted levels to play instead of the endless random spawns.
+    pub campaign: Option<Campaign>,
+    // Adapts the endless mode to how well the player does.
+    pub director: Option<Director>,
+    // Breaches cost no health, for testing.
+    pub god_mode: bool,
+}
+
+impl World {
+    pub fn new(size: Size) -> World {
+        World::with_seed(size, thread_rng().gen())
+    }
+
+    // Create a world whose enemy spawns are fully determined by `seed`.
+    pub fn with_seed(size: Size, seed: u64) -> World {
+        let (x, y) = (size.width / 2.0, size.height / 1.25);
+
+        let player = Player::new(x, y);
+
+        let spawn_layout = SpawnLayout::default();
+        let spawn_points = spawn_layout.points(size);
+
+        let rules = Rules::default();
+
+        let mut world = World {
+            size,
+            rules,
+            player,
+            game_status: GameStatus::Normal,
+            enemies: Vec::new(),
+            bullets: Vec::new(),
+            pickups: Vec::new(),
+            effects: Vec::new(),
+            particles: ParticleSystem::new(seed),
+            background: Background::new(&Background::default_layers(), seed),
+            events: Vec::new(),
+            spawn_layout,
+            spawn_points,
+            fire_bullet: false,
+            shoot_cooldown: rules.shoot_cooldown,
+            ammo: rules.start_ammo,
+            score: 0,
+            highscore: 0,
+            uptime: 0,
+            breaches: 0,
+            shots: 0,
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            campaign: None,
+            director: None,
+            god_mode: false,
+        };
+        world.start_thruster();
+        world
+    }
+
+    fn start_thruster(&mut self) {
+        let pos = self.thruster_pos();
+        self.particles.set_emitter(THRUSTER, pos, THRUSTER_RATE, ParticleConfig::thruster());
+    }
+
+    fn thruster_pos(&self) -> [f64; 2] {
+        [self.player.pos.x, self.player.pos.y + self.player.radius()]
+    }
+
+    pub fn update(&mut self, dt: f64) {
+        self.events.clear();
+        if self.game_status != GameStatus::Normal {
+            return;
+        };
+
+        self.uptime = self.uptime.saturating_add(1);
+
+        for effect in self.effects.iter_mut() {
+            effect.update(dt, self.size);
+        }
+        self.effects.retain(|effect| !effect.finished());
+        let thruster = self.thruster_pos();
+        self.particles.move_emitter(THRUSTER, thruster);
+        self.particles.update();
+        let pace = self.pace();
+        self.background.update(pace);
+
+        // Update Players health
+        for enemy in self.enemies.iter_mut() {
+            enemy.update(dt, self.size);
+            if enemy.defense_breached {
+                self.breaches += 1;
+                if !self.god_mode {
+                    self.player.health -= enemy.health;
+                }
+                self.player.hit();
+                let pos = enemy.position();
+                self.particles.burst([pos.x, pos.y.min(self.size.height)], BREACH_PARTICLES, &ParticleConfig::breach());
+                self.events.push(GameEvent::Breach { x: pos.x, y: pos.y, damage: enemy.health });
+                enemy.health = 0.0;
+            }
+        }
+
+        // If players health becomes zero: Dead.
+        if self.player.health <= 0.0
+        {
+            self.end_game(GameStatus::Died);
+            return;
+        }
+
+        // Update players parameters as other updattions use this parameters.
+        self.player.update(dt, self.size);
+        self.collect_pickups(dt);
+
+        if let Some(mut campaign) = self.campaign.take() {
+            let finished = campaign.step(self);
+            self.campaign = Some(campaign);
+            if finished {
+                self.end_game(GameStatus::Win);
+                return;
+            }
+        } else if !self.spawn_endless() {
+            return;
+        }
+
+        // Update shoot cooldown and fire bullet if reloaded.
+        if self.shoot_cooldown > 0 {
+            self.shoot_cooldown -= 1;
+        }
+
+        if self.fire_bullet && self.ammo > 0 {
+            self.fire_bullet = false;
+            self.bullets.push(Bullet::new(self.player.pos.x, self.player.pos.y));
+            self.ammo -= 1;
+            self.shots += 1;
+            self.events.push(GameEvent::Shot);
+        }
+
+        // Check for hits.
+        for bullet in self.bullets.iter_mut()
+        {
+            for enemy in self.enemies.iter_mut() {
+                if enemy.health == 0.0 {
+                    continue;
+                }
+                if bullet.collides(enemy) {
+                    enemy.health = 0.0;
+                    bullet.destroy = true;
+                    let pos = enemy.position();
+                    self.effects.push(Effect::explosion(pos.x, pos.y, enemy.kind.color()));
+                    self.events.push(GameEvent::Kill { kind: enemy.kind, x: pos.x, y: pos.y });
+                    self.particles.burst([pos.x, pos.y], EXPLOSION_PARTICLES, &ParticleConfig::explosion(enemy.kind.color()));
+                    let impact = bullet.position();
+                    self.particles.burst([impact.x, impact.y], IMPACT_PARTICLES, &ParticleConfig::impact());
+                    self.score += 1;
+                    self.ammo += self.rules.ammo_per_kill;
+                    break;
+                }
+            }
+        }
+
+        self.enemies.retain(|enemy| enemy.health > 0.0);
+
+        self.bullets.retain(|bullet| !bullet.destroy);
+        for bullet in self.bullets.iter_mut() {
+            bullet.update(dt, self.size);
+        }
+    }
+
+    // The endless mode: waves get more frequent the longer the player survives.
+    // Returns false once the player has survived long enough to win.
+    fn spawn_endless(&mut self) -> bool {
+        let rules = self.rules;
+        let mut difficulty = self.difficulty();
+
+        // If someone survives till difficulty 500, declare win.
+        if difficulty > rules.enemy_spawn_cooldown
+        {
+            self.end_game(GameStatus::Win);
+            return false;
+        }
+
+        // Max difficulty is spawning enemies after every 100 updates.
+        // Hence we cannot have difficulty less more than 400.
+        if difficulty > (rules.enemy_spawn_cooldown - rules.min_spawn_interval) {
+            difficulty = rules.enemy_spawn_cooldown - rules.min_spawn_interval;
+        }
+
+        self.spawn_enemies(difficulty);
+        true
+    }
+
+    fn collect_pickups(&mut self, dt: f64) {
+        for pickup in self.pickups.iter_mut() {
+            pickup.update(dt, self.size);
+            if self.player.collides(pickup) {
+                pickup.collected = true;
+                self.events.push(GameEvent::Pickup { kind: pickup.kind });
+                match pickup.kind {
+                    PickupKind::Ammo => self.ammo += PICKUP_AMMO,
+                    PickupKind::Health => {
+                        self.player.health = (self.player.health + PICKUP_HEALTH).min(PLAYER_HEALTH);
+                    },
+                }
+            }
+        }
+        self.pickups.retain(|pickup| !pickup.collected && !pickup.missed);
+    }
+
+    fn end_game(&mut self, status: GameStatus) {
+        self.game_status = status;
+        self.update_highscore();
+        self.events.push(GameEvent::GameOver { won: status == GameStatus::Win });
+    }
+
+    // Keep the in-memory highscore up to date. Persisting it is left to the
+    // frontend so headless runs never touch the disk.
+    fn update_highscore(&mut self) {
+        if self.score > self.highscore {
+            self.highscore = self.score;
+        }
+    }
+
+    fn spawn_enemies (&mut self, difficulty: u64) {
+        let interval = self.rules.enemy_spawn_cooldown - difficulty;
+        if let Some(mut director) = self.director.take() {
+            director.observe(self);
+            if director.wave_due(interval) {
+                self.spawn_directed_wave(&director);
+            }
+            self.director = Some(director);
+            return;
+        }
+
+        if self.uptime % interval == 1
+        {
+            // Every spawn point has its own random number.
+            for point in self.spawn_points.iter() {
+                let roll: f32 = self.rng.gen();
+                if roll > 1.0 - self.rules.spawn_chance {
+                    self.enemies.push(Enemy::new(point.x, point.y).from_edge(point.from));
+                }
+            }
+        }
+    }
+
+    fn spawn_directed_wave(&mut self, director: &Director) {
+        for point in self.spawn_points.iter() {
+            let roll: f32 = self.rng.gen();
+            if roll > 1.0 - self.rules.spawn_chance {
+                let kind = director.enemy_kind(&mut self.rng);
+                self.enemies.push(Enemy::with_kind(point.x, point.y, kind, Movement::Straight).from_edge(point.from));
+            }
+        }
+
+        // Pickups always fall from the top, away from the borders.
+        if let Some(kind) = director.pickup(&mut self.rng) {
+            let x = self.rng.gen_range(0.1..0.9) * self.size.width;
+            self.pickups.push(Pickup::new(x, 0.0, kind));
+        }
+    }
+
+    pub fn set_spawn_layout(&mut self, layout: SpawnLayout) {
+        self.spawn_points = layout.points(self.size);
+        self.spawn_layout = layout;
+    }
+
+    // Change the playfield size, moving the spawn points along with it.
+    pub fn resize(&mut self, size: Size) {
+        self.size = size;
+        self.spawn_points = self.spawn_layout.points(size);
+        geom::restrict_to_bounds(&mut self.player.pos, [size.width, size.height]);
+    }
+
+    pub fn reset(&mut self) {
+        self.player.reset(self.size.width / 2.0, self.size.height / 1.25);
+        self.enemies.clear();
+        self.bullets.clear();
+        self.pickups.clear();
+        self.effects.clear();
+        self.particles.reset(self.seed);
+        self.start_thruster();
+        self.game_status = GameStatus::Normal;
+        self.fire_bullet = false;
+        self.shoot_cooldown = self.rules.shoot_cooldown;
+        self.ammo = self.rules.start_ammo;
+        self.score = 0;
+        self.uptime = 0;
+        self.breaches = 0;
+        self.shots = 0;
+        if let Some(director) = self.director.as_mut() {
+            director.reset();
+        }
+        if let Some(campaign) = self.campaign.as_mut() {
+            campaign.reset();
+        }
+    }
+
+    // Restart the game with a fresh generator seeded from `seed`.
+    pub fn reset_with_seed(&mut self, seed: u64) {
+        self.reset();
+        self.seed = seed;
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self.restart_effects();
+    }
+
+    // Start particles and background over from the seed, e.g. after loading
+    // a saved world that doesn't include them.
+    pub fn restart_effects(&mut self) {
+        self.effects.clear();
+        self.particles.reset(self.seed);
+        self.start_thruster();
+        self.background = Background::new(&self.background.defs(), self.seed);
+    }
+
+    // Stable hash of the simulation state: positions, health, score, ammo,
+    // cooldowns and the generator. Two runs that agree on it agree on the game.
+    pub fn checksum(&self) -> u64 {
+        let mut sum = Checksum::new();
+        sum.u64(self.uptime);
+        sum.u64(self.game_status as u64);
+        sum.u64(u64::from(self.score));
+        sum.u64(u64::from(self.ammo));
+        sum.u64(u64::from(self.shoot_cooldown));
+        sum.f64(self.player.pos.x);
+        sum.f64(self.player.pos.y);
+        sum.f64(self.player.health);
+        sum.u64(self.enemies.len() as u64);
+        for enemy in self.enemies.iter() {
+            let pos = enemy.position();
+            sum.f64(pos.x);
+            sum.f64(pos.y);
+            sum.f64(enemy.health);
+        }
+        sum.u64(self.bullets.len() as u64);
+        for bullet in self.bullets.iter() {
+            let pos = bullet.position();
+            sum.f64(pos.x);
+            sum.f64(pos.y);
+        }
+        sum.u64(self.pickups.len() as u64);
+        for pickup in self.pickups.iter() {
+            let pos = pickup.position();
+            sum.f64(pos.x);
+            sum.f64(pos.y);
+        }
+        sum.bytes(&self.rng.get_seed());
+        sum.u64(self.rng.get_stream());
+        sum.bytes(&self.rng.get_word_pos().to_le_bytes());
+        sum.finish()
+    }
+
+    // Rises by one every `difficulty_interval` updates.
+    pub fn difficulty(&self) -> u64 {
+        self.uptime / self.rules.difficulty_interval.max(1)
+    }
+
+    // How hectic the game is: 1 at the start, rising to 3 as the endless mode
+    // nears its end and sped up further by the director.
+    pub fn pace(&self) -> f64 {
+        let difficulty = self.difficulty();
+        let progress = difficulty as f64 / self.rules.enemy_spawn_cooldown.max(1) as f64;
+        let director = self.director.as_ref().map_or(1.0, |director| director.knobs.spawn_rate);
+        (1.0 + 2.0 * progress).min(3.0) * director
+    }
+
+    // Sanity checks on the world state, used by soak tests to catch bugs that
+    // would otherwise go unnoticed.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if self.game_status == GameStatus::Normal && self.player.health <= 0.0 {
+            return Err(format!("player alive with health {}", self.player.health));
+        }
+        if self.enemies.len() > MAX_ENEMIES {
+            return Err(format!("{} enemies alive", self.enemies.len()));
+        }
+        if self.bullets.len() > MAX_BULLETS {
+            return Err(format!("{} bullets alive", self.bullets.len()));
+        }
+
+        let pos = &self.player.pos;
+        if !(0.0..=self.size.width).contains(&pos.x) || !(0.0..=self.size.height).contains(&pos.y) {
+            return Err(format!("player out of bounds at {:?}", pos));
+        }
+        for enemy in self.enemies.iter() {
+            let pos = enemy.position();
+            if !pos.x.is_finite() || !pos.y.is_finite() || enemy.health < 0.0 {
+                return Err(format!("invalid enemy at {:?} with health {}", pos, enemy.health));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn input (&mut self, button: &Button, press_event: bool) {
+        // Restart game on mouse click after it has ended.
+        if !press_event {
+            if let Button::Mouse(_) = *button {
+                if self.game_status != GameStatus::Normal {
+                    self.reset();
+                }
+                return;
+            }
+        }
+
+        let mut direction: Direction = self.player.dir;
+        if let Button::Keyboard(key) = *button {
+            match key {
+                Key::Up => direction = geom::Direction::NORTH,
+                Key::Down => direction = geom::Direction::SOUTH,
+                Key::Right => direction = geom::Direction::EAST,
+                Key::Left => direction = geom::Direction::WEST,
+                Key::Space => {
+                    if self.shoot_cooldown == 0 && press_event{
+                        self.fire_bullet = true;
+                    }
+                    return;
+                }, // Fire bullets!
+                _ => (), // Ignore all other
+            }
+        }
+
+        if direction == self.player.dir && !press_event {
+            self.player.stop_movement = true;
+        }
+        else if press_event {
+            self.player.stop_movement = false;
+        }
+        if press_event {
+            self.player.stop_movement = false;
+            self.player.movement(direction);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use basic_game::anim::{Animation, Easing, Frame, Playback, Tween};
+
+fn frames() -> Vec<Frame> {
+    vec![Frame::new("a", 2), Frame::new("b", 1)]
+}
+
+#[test]
+fn looping_animation_wraps() {
+    let mut animation = Animation::new(frames(), Playback::Loop);
+    let mut shown = Vec::new();
+    for _ in 0..6 {
+        shown.push(animation.frame().unwrap().sprite.clone());
+        animation.step();
+    }
+    assert_eq!(shown, ["a", "a", "b", "a", "a", "b"]);
+    assert!(!animation.finished());
+}
+
+#[test]
+fn once_animation_stops_and_calls_back() {
+    let calls = Rc::new(Cell::new(0));
+    let counter = calls.clone();
+    let mut animation = Animation::new(frames(), Playback::Once)
+        .on_finish(Box::new(move || counter.set(counter.get() + 1)));
+
+    let finished_at = (1..=10).find(|_| animation.step());
+    assert_eq!(finished_at, Some(3));
+    for _ in 0..10 {
+        animation.step();
+    }
+    assert!(animation.finished());
+    assert_eq!(animation.frame().unwrap().sprite, "b");
+    assert_eq!(calls.get(), 1);
+}
+
+#[test]
+fn easing_keeps_end_points() {
+    let all = [Easing::Linear, Easing::QuadIn, Easing::QuadOut, Easing::QuadInOut,
+               Easing::CubicIn, Easing::CubicOut, Easing::SineInOut, Easing::BackOut];
+    for easing in all {
+        assert!(easing.apply(0.0).abs() < 1e-9, "{:?}", easing);
+        assert!((easing.apply(1.0) - 1.0).abs() < 1e-9, "{:?}", easing);
+    }
+    assert_eq!(Easing::QuadIn.apply(0.5), 0.25);
+    assert!(Easing::BackOut.apply(0.8) > 1.0);
+}
+
+#[test]
+fn tween_moves_in_updates() {
+    let mut position = Tween::new([0.0, 0.0], [10.0, 20.0], 4, Easing::Linear);
+    position.step();
+    position.step();
+    assert_eq!(position.value(), [5.0, 10.0]);
+    for _ in 0..10 {
+        position.step();
+    }
+    assert!(position.done());
+    assert_eq!(position.value(), [10.0, 20.0]);
+
+    let mut color = Tween::new([1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 0.0], 2, Easing::Linear);
+    color.step();
+    assert_eq!(color.value(), [0.5, 0.0, 0.5, 0.5]);
+}
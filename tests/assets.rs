@@ -0,0 +1,80 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use basic_game::cli::Options;
+use basic_game::config::assets::{find_assets, AssetId, AssetKind, AssetManager, DEFAULT_FONT, HUD_FONT};
+use opengl_graphics::{GlyphCache, TextureSettings};
+
+// Fresh assets folder for one test.
+fn scratch(name: &str, manifest: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("basic_game_assets_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("sounds")).unwrap();
+    fs::write(root.join("manifest.json"), manifest).unwrap();
+    root
+}
+
+#[test]
+fn built_in_font_matches_the_asset() {
+    let manager = AssetManager::open(Path::new("src/assets")).unwrap();
+    manager.check().unwrap();
+    let file = fs::read(manager.path(AssetKind::Font, HUD_FONT).unwrap()).unwrap();
+    assert_eq!(file, DEFAULT_FONT);
+    assert!(GlyphCache::from_bytes(DEFAULT_FONT, (), TextureSettings::new()).is_ok());
+}
+
+#[test]
+fn explicit_assets_folder_is_checked() {
+    assert_eq!(find_assets(Some(Path::new("src/assets"))).unwrap(), Path::new("src/assets"));
+    let err = find_assets(Some(Path::new("no/such/folder"))).unwrap_err();
+    assert!(err.contains("no/such/folder"), "{}", err);
+}
+
+#[test]
+fn assets_folder_from_command_line() {
+    let options = Options::parse(["--assets".to_string(), "data/assets".to_string()].into_iter()).unwrap();
+    assert_eq!(options.assets.as_deref(), Some(Path::new("data/assets")));
+}
+
+#[test]
+fn missing_assets_are_reported_together() {
+    let root = scratch("missing", r#"{
+        "fonts": { "hud": "fonts/none.ttf" },
+        "textures": { "ship": "sprites/ship.png" },
+        "sounds": { "shoot": "sounds/shoot.wav" }
+    }"#);
+    fs::write(root.join("sounds/shoot.wav"), b"RIFF").unwrap();
+
+    let manager = AssetManager::open(&root).unwrap();
+    let err = manager.check().unwrap_err();
+    assert!(err.contains("font 'hud'") && err.contains("texture 'ship'"), "{}", err);
+    assert!(!err.contains("shoot"), "{}", err);
+    assert_eq!(manager.sound("shoot"), Some(&b"RIFF"[..]));
+    assert!(manager.path(AssetKind::Texture, "ship").is_none());
+}
+
+#[test]
+fn bad_manifest_is_an_error() {
+    let root = scratch("bad", r#"{ "music": {} }"#);
+    assert!(AssetManager::open(&root).is_err());
+}
+
+#[test]
+fn changed_files_are_reloaded() {
+    let root = scratch("reload", r#"{ "sounds": { "hit": "sounds/hit.wav" } }"#);
+    let sound = root.join("sounds/hit.wav");
+    fs::write(&sound, b"old").unwrap();
+
+    let mut manager = AssetManager::open(&root).unwrap();
+    assert!(manager.reload_changed().unwrap().is_empty());
+
+    fs::write(&sound, b"new").unwrap();
+    // Make sure the change is visible even on filesystems with coarse timestamps.
+    let later = SystemTime::now() + Duration::from_secs(5);
+    fs::File::options().write(true).open(&sound).unwrap().set_modified(later).unwrap();
+
+    let changed = manager.reload_changed().unwrap();
+    assert_eq!(changed, vec![AssetId { kind: AssetKind::Sound, name: "hit".to_string() }]);
+    assert_eq!(manager.sound("hit"), Some(&b"new"[..]));
+}
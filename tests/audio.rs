@@ -0,0 +1,122 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::{env, fs};
+
+use basic_game::audio::{Audio, AudioBackend, Sfx, Sound, Volumes, WavFileBackend, MUSIC_GAME, MUSIC_GAME_OVER, SAMPLE_RATE};
+use basic_game::models::enemy::EnemyKind;
+use basic_game::world::GameEvent;
+
+// Keeps everything it is given where the test can look at it.
+struct Capture(Rc<RefCell<Vec<f32>>>);
+
+impl AudioBackend for Capture {
+    fn submit(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.0.borrow_mut().extend_from_slice(samples);
+        Ok(())
+    }
+}
+
+fn capture(volumes: Volumes) -> (Audio, Rc<RefCell<Vec<f32>>>) {
+    let out = Rc::new(RefCell::new(Vec::new()));
+    (Audio::new(Box::new(Capture(out.clone())), volumes), out)
+}
+
+fn tone(value: f32, seconds: f64) -> Sound {
+    Sound { rate: SAMPLE_RATE, samples: vec![value; (seconds * f64::from(SAMPLE_RATE)) as usize].into() }
+}
+
+#[test]
+fn wav_round_trip() {
+    let sound = Sound { rate: 22_050, samples: vec![0.0, 0.5, -0.5, 1.0, -1.0].into() };
+    let decoded = Sound::from_wav(&sound.to_wav()).unwrap();
+    assert_eq!(decoded.rate, 22_050);
+    assert_eq!(decoded.samples.len(), 5);
+    for (a, b) in sound.samples.iter().zip(decoded.samples.iter()) {
+        assert!((a - b).abs() < 0.001);
+    }
+    assert!(Sound::from_wav(b"not a wav").is_err());
+}
+
+#[test]
+fn wav_file_is_written_as_it_plays() {
+    let path = env::temp_dir().join(format!("basic_game_audio_{}.wav", std::process::id()));
+    let mut backend = WavFileBackend::create(path.clone()).unwrap();
+    backend.submit(&[0.5; 10_000]).unwrap();
+    // Samples reach the file before the sizes are known.
+    assert!(fs::metadata(&path).unwrap().len() > 10_000);
+    backend.submit(&[-0.5; 50]).unwrap();
+    drop(backend);
+
+    let sound = Sound::from_wav(&fs::read(&path).unwrap()).unwrap();
+    assert_eq!(sound.rate, SAMPLE_RATE);
+    assert_eq!(sound.samples.len(), 10_050);
+    assert!((sound.samples[0] - 0.5).abs() < 0.001);
+    assert!((sound.samples[10_049] + 0.5).abs() < 0.001);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn mixer_follows_elapsed_time() {
+    let (mut audio, out) = capture(Volumes::default());
+    for _ in 0..60 {
+        audio.advance(1.0 / 60.0).unwrap();
+    }
+    let len = out.borrow().len() as i64;
+    assert!((len - i64::from(SAMPLE_RATE)).abs() <= 1);
+    assert!(out.borrow().iter().all(|sample| *sample == 0.0));
+}
+
+#[test]
+fn events_play_effects() {
+    let (mut audio, out) = capture(Volumes { master: 1.0, music: 1.0, sfx: 0.5 });
+    for sfx in Sfx::ALL {
+        audio.set_sound(sfx.name(), tone(0.2, 0.1));
+    }
+
+    audio.handle(&[GameEvent::Shot, GameEvent::Kill { kind: EnemyKind::Basic, x: 0.0, y: 0.0 }]);
+    assert_eq!(audio.playing(), 3);
+    audio.advance(0.01).unwrap();
+    assert!((out.borrow()[0] - 0.3).abs() < 0.001);
+
+    // Effects stop once they have played through.
+    audio.advance(0.2).unwrap();
+    assert_eq!(audio.playing(), 0);
+}
+
+#[test]
+fn zero_volume_is_silent() {
+    let (mut audio, out) = capture(Volumes { master: 0.0, music: 1.0, sfx: 1.0 });
+    audio.set_sound(Sfx::Shoot.name(), tone(0.5, 0.1));
+    audio.set_sound(MUSIC_GAME, tone(0.5, 0.1));
+    audio.play(Sfx::Shoot);
+    audio.play_music(MUSIC_GAME, 0.0);
+    audio.advance(0.05).unwrap();
+    assert!(out.borrow().iter().all(|sample| *sample == 0.0));
+}
+
+#[test]
+fn music_crossfades() {
+    let (mut audio, out) = capture(Volumes { master: 1.0, music: 1.0, sfx: 1.0 });
+    audio.set_sound(MUSIC_GAME, tone(0.5, 0.1));
+    audio.set_sound(MUSIC_GAME_OVER, tone(-0.5, 0.1));
+
+    audio.play_music(MUSIC_GAME, 0.0);
+    audio.advance(0.5).unwrap();
+    assert_eq!(audio.music(), Some(MUSIC_GAME));
+    // The short track loops.
+    assert!((out.borrow().last().unwrap() - 0.5).abs() < 0.001);
+
+    audio.handle(&[GameEvent::GameOver { won: false }]);
+    assert_eq!(audio.music(), Some(MUSIC_GAME_OVER));
+    out.borrow_mut().clear();
+    audio.advance(0.5).unwrap();
+    // Halfway through the fade the two tracks cancel out.
+    let middle = out.borrow()[out.borrow().len() / 2];
+    assert!(middle.abs() < 0.5);
+
+    audio.advance(2.0).unwrap();
+    assert!((out.borrow().last().unwrap() + 0.5).abs() < 0.001);
+
+    audio.stop_music(0.0);
+    assert_eq!(audio.music(), None);
+}
@@ -0,0 +1,63 @@
+use basic_game::background::{Background, LayerDef};
+use basic_game::world::World;
+use piston::window::Size;
+
+const SIZE: Size = Size { width: 400.0, height: 600.0 };
+
+fn stars(background: &Background) -> Vec<[f64; 3]> {
+    background.layers.iter().flat_map(|layer| layer.points(SIZE)).collect()
+}
+
+#[test]
+fn stars_are_seeded() {
+    let layers = Background::default_layers();
+    assert_eq!(stars(&Background::new(&layers, 3)), stars(&Background::new(&layers, 3)));
+    assert_ne!(stars(&Background::new(&layers, 3)), stars(&Background::new(&layers, 4)));
+}
+
+#[test]
+fn layers_scroll_at_their_own_speed() {
+    let layers = [
+        LayerDef::Stars { count: 1, speed: 0.5, size: 1.0, color: [1.0; 4] },
+        LayerDef::Stars { count: 1, speed: 2.0, size: 1.0, color: [1.0; 4] },
+    ];
+    let mut background = Background::new(&layers, 1);
+    for _ in 0..10 {
+        background.update(1.0);
+    }
+    background.update(2.0);
+    assert_eq!(background.layers[0].offset, 6.0);
+    assert_eq!(background.layers[1].offset, 24.0);
+}
+
+#[test]
+fn stars_stay_on_screen() {
+    let mut background = Background::new(&Background::default_layers(), 9);
+    for _ in 0..5000 {
+        background.update(3.0);
+        for layer in background.layers.iter() {
+            if let LayerDef::Stars { .. } = layer.def {
+                for [x, y, _] in layer.points(SIZE) {
+                    assert!((0.0..=SIZE.width).contains(&x) && (0.0..SIZE.height).contains(&y));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn pace_rises_during_a_game() {
+    let mut world = World::with_seed(SIZE, 1);
+    assert_eq!(world.pace(), 1.0);
+    world.uptime = world.rules.difficulty_interval * world.rules.enemy_spawn_cooldown / 2;
+    assert_eq!(world.pace(), 2.0);
+}
+
+#[test]
+fn layers_from_json() {
+    let layers: Vec<LayerDef> = serde_json::from_str(r#"[
+        { "type": "texture", "texture": "clouds", "speed": 0.3 },
+        { "type": "stars", "count": 10, "speed": 1, "size": 2, "color": [1, 1, 1, 1] }
+    ]"#).unwrap();
+    assert_eq!(layers[0], LayerDef::Texture { texture: "clouds".to_string(), speed: 0.3, tint: [1.0; 4] });
+}
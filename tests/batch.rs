@@ -0,0 +1,51 @@
+use basic_game::batch::{self, BatchConfig, Policy};
+use basic_game::world::Rules;
+
+fn config(threads: usize) -> BatchConfig {
+    BatchConfig { games: 8, first_seed: 100, threads, policy: Policy::Bot, max_ticks: 5000 }
+}
+
+#[test]
+fn results_do_not_depend_on_thread_count() {
+    let single = batch::run(Rules::default(), &config(1));
+    let many = batch::run(Rules::default(), &config(3));
+    assert_eq!(batch::to_csv(&[single]), batch::to_csv(&[many]));
+}
+
+#[test]
+fn sweep_expands_every_combination() {
+    let sweeps = vec![
+        ("spawn_chance".to_string(), vec!["0.1".to_string(), "0.5".to_string()]),
+        ("start_ammo".to_string(), vec!["5".to_string(), "10".to_string(), "15".to_string()]),
+    ];
+    let rules = batch::sweep(Rules::default(), &sweeps).unwrap();
+    assert_eq!(rules.len(), 6);
+    assert_eq!(rules[5].spawn_chance, 0.5);
+    assert_eq!(rules[5].start_ammo, 15);
+
+    let bad = vec![("no_such_rule".to_string(), vec!["1".to_string()])];
+    assert!(batch::sweep(Rules::default(), &bad).is_err());
+}
+
+#[test]
+fn overrides_are_checked_together() {
+    // Invalid after the first override alone, fine after both.
+    let mut rules = Rules::default();
+    rules.set("enemy_spawn_cooldown", "50").unwrap();
+    assert!(rules.validate().is_err());
+    rules.set("min_spawn_interval", "20").unwrap();
+    assert_eq!(rules.validate(), Ok(()));
+
+    let sweeps = vec![
+        ("enemy_spawn_cooldown".to_string(), vec!["50".to_string()]),
+        ("min_spawn_interval".to_string(), vec!["20".to_string(), "60".to_string()]),
+    ];
+    assert!(batch::sweep(Rules::default(), &sweeps[..1]).is_err());
+    assert!(batch::sweep(Rules::default(), &sweeps).is_err());
+    let mut valid = sweeps.clone();
+    valid[1].1.pop();
+    assert_eq!(batch::sweep(Rules::default(), &valid).unwrap()[0].min_spawn_interval, 20);
+
+    rules.set("min_spawn_interval", "0").unwrap();
+    assert!(rules.validate().is_err());
+}
@@ -0,0 +1,11 @@
+use basic_game::bot;
+use basic_game::world::World;
+use piston::window::Size;
+
+#[test]
+fn soak_keeps_invariants() {
+    let mut world = World::new(Size { width: 400.0, height: 600.0 });
+    let report = bot::soak(&mut world, 50_000).unwrap();
+    assert_eq!(report.ticks, 50_000);
+    assert!(report.best_score > 0 || world.score > 0);
+}
@@ -0,0 +1,67 @@
+use basic_game::camera::Camera;
+use basic_game::models::enemy::Enemy;
+use basic_game::world::{GameEvent, World};
+use piston::window::Size;
+
+const SIZE: Size = Size { width: 400.0, height: 600.0 };
+
+#[test]
+fn trauma_wears_off() {
+    let mut camera = Camera::new(true);
+    camera.add_trauma(0.5);
+    camera.add_trauma(0.8);
+    assert_eq!(camera.trauma, 1.0);
+
+    camera.update();
+    let (offset, angle) = camera.shake();
+    assert!(offset != [0.0, 0.0] || angle != 0.0);
+    assert!(offset[0].abs() <= 10.0 && offset[1].abs() <= 10.0 && angle.abs() <= 2.0);
+
+    for _ in 0..100 {
+        camera.update();
+    }
+    assert_eq!(camera.trauma, 0.0);
+    assert_eq!(camera.shake(), ([0.0, 0.0], 0.0));
+}
+
+#[test]
+fn shake_can_be_turned_off() {
+    let mut camera = Camera::new(false);
+    camera.add_trauma(1.0);
+    camera.update();
+    assert_eq!(camera.trauma, 0.0);
+    assert_eq!(camera.shake(), ([0.0, 0.0], 0.0));
+}
+
+#[test]
+fn zoom_and_offset_map_to_the_playfield() {
+    let mut camera = Camera::default();
+    assert_eq!(camera.to_world([10.0, 20.0], SIZE), [10.0, 20.0]);
+
+    camera.zoom = 2.0;
+    camera.offset = [50.0, 0.0];
+    // The centre of the screen shows the offset point.
+    assert_eq!(camera.to_world([200.0, 300.0], SIZE), [250.0, 300.0]);
+    assert_eq!(camera.to_world([0.0, 0.0], SIZE), [150.0, 150.0]);
+}
+
+#[test]
+fn world_reports_kills_and_breaches() {
+    let mut world = World::with_seed(SIZE, 1);
+    world.rules.enemy_spawn_cooldown = u64::MAX;
+    world.rules.spawn_chance = 0.0;
+
+    let (x, y) = (world.player.pos.x, world.player.pos.y);
+    world.enemies.push(Enemy::new(x, y - 100.0));
+    world.enemies.push(Enemy::new(20.0, SIZE.height));
+    world.shoot_cooldown = 0;
+    world.fire_bullet = true;
+
+    let mut events = Vec::new();
+    for _ in 0..200 {
+        world.update(1.0 / 120.0);
+        events.extend(world.events.iter().copied());
+    }
+    assert!(events.iter().any(|event| matches!(event, GameEvent::Breach { damage, .. } if *damage == 100.0)));
+    assert!(events.iter().any(|event| matches!(event, GameEvent::Kill { .. })));
+}
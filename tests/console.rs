@@ -0,0 +1,143 @@
+use std::env;
+use std::fs;
+
+use basic_game::console::{Command, Console, Spawn};
+use basic_game::models::enemy::{Enemy, EnemyKind};
+use basic_game::world::World;
+use piston::window::Size;
+
+const SIZE: Size = Size { width: 400.0, height: 600.0 };
+
+#[test]
+fn parses_commands() {
+    assert_eq!(Command::parse("  # comment").unwrap(), None);
+    assert_eq!(
+        Command::parse("spawn fast 10 20 3").unwrap(),
+        Some(Command::Spawn { what: Spawn::Enemy(EnemyKind::Fast), pos: Some([10.0, 20.0]), count: 3 }),
+    );
+    assert_eq!(Command::parse("god off").unwrap(), Some(Command::God(Some(false))));
+    assert_eq!(Command::parse("step").unwrap(), Some(Command::Step(1)));
+
+    assert!(Command::parse("spawn dragon").unwrap_err().contains("dragon"));
+    assert!(Command::parse("spawn basic 4000000000").unwrap_err().contains("1000"));
+    assert!(Command::parse("spawn ammo 1 2 1001").is_err());
+    assert!(Command::parse("spawn basic 1000").is_ok());
+    assert!(Command::parse("set lives 3").is_err());
+    assert!(Command::parse("timescale 50").is_err());
+    assert!(Command::parse("uptime").unwrap_err().starts_with("Usage: uptime"));
+    assert!(Command::parse("step 100000").is_ok());
+    assert!(Command::parse("step 18446744073709551615").is_err());
+    assert!(Command::parse("set health nan").is_err());
+    assert!(Command::parse("set score inf").is_err());
+}
+
+#[test]
+fn rejects_values_the_world_cannot_take() {
+    let mut world = World::with_seed(SIZE, 1);
+    let mut console = Console::new();
+
+    assert!(console.run("difficulty 400000000000000000", &mut world).is_err());
+    let max = world.rules.enemy_spawn_cooldown;
+    assert!(console.run(&format!("difficulty {}", max + 1), &mut world).is_err());
+    console.run(&format!("difficulty {}", max), &mut world).unwrap();
+
+    // The update counter stops at its largest value.
+    console.run("uptime 18446744073709551615", &mut world).unwrap();
+    console.run("step 2", &mut world).unwrap();
+
+    // The enemy limit holds across commands.
+    let mut world = World::with_seed(SIZE, 1);
+    console.run("spawn basic 600", &mut world).unwrap();
+    assert!(console.run("spawn basic 600", &mut world).is_err());
+    assert_eq!(world.enemies.len(), 600);
+    console.run("spawn ammo 600", &mut world).unwrap();
+}
+
+#[test]
+fn commands_change_the_world() {
+    let mut world = World::with_seed(SIZE, 1);
+    let mut console = Console::new();
+
+    console.run("spawn heavy 100 50 2", &mut world).unwrap();
+    console.run("spawn health", &mut world).unwrap();
+    assert_eq!(world.enemies.len(), 2);
+    assert_eq!(world.pickups.len(), 1);
+
+    console.run("set ammo 99", &mut world).unwrap();
+    console.run("set score 7", &mut world).unwrap();
+    assert_eq!((world.ammo, world.score), (99, 7));
+
+    console.run("difficulty 4", &mut world).unwrap();
+    assert_eq!(world.difficulty(), 4);
+
+    console.run("timescale 0.5", &mut world).unwrap();
+    assert_eq!(console.time_scale, 0.5);
+
+    console.run("step 5", &mut world).unwrap();
+    assert_eq!(world.uptime, 4 * world.rules.difficulty_interval + 5);
+}
+
+#[test]
+fn god_mode_ignores_breaches() {
+    let mut world = World::with_seed(SIZE, 1);
+    let mut console = Console::new();
+    console.run("god on", &mut world).unwrap();
+    world.enemies.push(Enemy::new(100.0, SIZE.height - 0.5));
+    world.update(1.0 / 120.0);
+    assert_eq!(world.breaches, 1);
+    assert_eq!(world.player.health, 1000.0);
+}
+
+#[test]
+fn runs_scripts_and_stdin() {
+    let path = env::temp_dir().join(format!("console_script_{}.txt", std::process::id()));
+    fs::write(&path, "# setup\nset health 10\nspawn basic\n").unwrap();
+    let mut world = World::with_seed(SIZE, 1);
+    let mut console = Console::new();
+    console.run_script(&path, &mut world).unwrap();
+    assert_eq!(world.player.health, 10.0);
+    assert_eq!(world.enemies.len(), 1);
+
+    fs::write(&path, "set health 10\nfly away\n").unwrap();
+    let err = console.run_script(&path, &mut world).unwrap_err();
+    assert!(err.ends_with(":2: Unknown command 'fly', try 'help'"), "{}", err);
+    fs::remove_file(&path).unwrap();
+
+    console.log.clear();
+    let mut output = Vec::new();
+    console.run_lines("set score 3\nnope\n".as_bytes(), &mut output, &mut world).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output, "score = 3\nerror: Unknown command 'nope', try 'help'\n");
+}
+
+#[test]
+fn typing_and_history() {
+    let mut world = World::with_seed(SIZE, 1);
+    let mut console = Console::new();
+    console.type_text("set ammo 5`");
+    console.submit(&mut world);
+    assert_eq!(world.ammo, 5);
+    assert_eq!(console.log, ["> set ammo 5", "ammo = 5"]);
+
+    console.browse(true);
+    assert_eq!(console.line, "set ammo 5");
+    console.backspace();
+    console.type_text("6");
+    console.submit(&mut world);
+    assert_eq!(world.ammo, 6);
+}
+
+#[test]
+fn flags_commands_that_change_the_world() {
+    let mut world = World::with_seed(SIZE, 1);
+    let mut console = Console::new();
+    for line in ["help", "timescale 2", "# note", "spawn nothing"] {
+        let _ = console.run(line, &mut world);
+        assert!(!console.world_changed, "{}", line);
+    }
+    for line in ["spawn basic", "set score 3", "uptime 10", "difficulty 1", "god", "step"] {
+        console.world_changed = false;
+        console.run(line, &mut world).unwrap();
+        assert!(console.world_changed, "{}", line);
+    }
+}
@@ -0,0 +1,38 @@
+use std::time::{Duration, Instant};
+
+use basic_game::debug::DebugOverlay;
+use basic_game::models::enemy::Enemy;
+use basic_game::world::World;
+use piston::window::Size;
+
+#[test]
+fn counts_frames_and_updates_per_second() {
+    let mut overlay = DebugOverlay::new(false);
+    let start = Instant::now();
+    for i in 0..=120 {
+        let now = start + Duration::from_secs_f64(i as f64 / 120.0);
+        overlay.update(now);
+        if i % 4 == 0 {
+            overlay.frame(now);
+        }
+    }
+    assert!((overlay.ups - 120.0).abs() < 2.0);
+    assert!((overlay.fps - 30.0).abs() < 2.0);
+
+    overlay.toggle();
+    assert!(overlay.enabled);
+}
+
+#[test]
+fn lines_describe_the_world() {
+    let mut world = World::with_seed(Size { width: 400.0, height: 600.0 }, 1);
+    world.enemies.push(Enemy::new(50.0, 50.0));
+    world.enemies.push(Enemy::new(80.0, 50.0));
+    world.uptime = world.rules.difficulty_interval * 3 + 1;
+    assert_eq!(world.difficulty(), 3);
+
+    let text = DebugOverlay::new(true).lines(&world).join("\n");
+    assert!(text.contains(&format!("Tick {}", world.uptime)));
+    assert!(text.contains("Difficulty 3"));
+    assert!(text.contains("Enemies 2"));
+}
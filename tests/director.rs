@@ -0,0 +1,71 @@
+use basic_game::bot::Bot;
+use basic_game::director::{Director, DirectorConfig};
+use basic_game::world::World;
+use piston::window::Size;
+
+fn decisions(seed: u64, config: DirectorConfig) -> Vec<String> {
+    let mut world = World::with_seed(Size { width: 400.0, height: 600.0 }, seed);
+    world.director = Some(Director::new(config));
+    let mut bot = Bot::new();
+    for _ in 0..20_000 {
+        for (button, press) in bot.act(&world) {
+            world.input(&button, press);
+        }
+        world.update(1.0 / 120.0);
+    }
+    world.director.unwrap().decisions.iter().map(|d| d.to_string()).collect()
+}
+
+#[test]
+fn same_seed_same_decisions() {
+    let first = decisions(3, DirectorConfig::default());
+    assert!(!first.is_empty());
+    assert_eq!(first, decisions(3, DirectorConfig::default()));
+}
+
+#[test]
+fn knobs_stay_within_limits() {
+    let config = DirectorConfig {
+        interval: 120,
+        step: 0.5,
+        min_spawn_rate: 0.9,
+        max_spawn_rate: 1.1,
+        max_special_share: 0.2,
+        max_pickup_chance: 0.1,
+        ..DirectorConfig::default()
+    };
+    let mut world = World::with_seed(Size { width: 400.0, height: 600.0 }, 9);
+    world.director = Some(Director::new(config));
+    for _ in 0..10_000 {
+        world.update(1.0 / 120.0);
+        let knobs = world.director.as_ref().unwrap().knobs;
+        assert!((0.9..=1.1).contains(&knobs.spawn_rate));
+        assert!((0.0..=0.2).contains(&knobs.special_share));
+        assert!((0.0..=0.1).contains(&knobs.pickup_chance));
+    }
+}
+
+#[test]
+fn lowered_score_does_not_underflow() {
+    let mut world = World::with_seed(Size { width: 400.0, height: 600.0 }, 3);
+    let config = DirectorConfig::default();
+    let interval = config.interval;
+    let mut director = Director::new(config);
+    world.score = 50;
+    world.shots = 80;
+    world.uptime = interval;
+    director.observe(&world);
+
+    // Rewritten behind the director's back.
+    world.score = 10;
+    world.shots = 0;
+    world.uptime = 2 * interval;
+    let decision = director.observe(&world).unwrap();
+    assert_eq!((decision.performance.kills, decision.performance.shots), (0, 0));
+
+    // Restarting the window measures from the new values.
+    director.restart_window(&world);
+    world.score = 14;
+    world.uptime = 3 * interval;
+    assert_eq!(director.observe(&world).unwrap().performance.kills, 4);
+}
@@ -0,0 +1,46 @@
+use basic_game::cli::Options;
+use basic_game::config::{self, DisplaySettings};
+use opengl_graphics::OpenGL;
+
+fn parse(args: &[&str]) -> Result<Options, String> {
+    Options::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn parses_opengl_versions() {
+    assert_eq!(config::parse_opengl("2.1"), Ok(OpenGL::V2_1));
+    assert_eq!(config::parse_opengl("3.2"), Ok(OpenGL::V3_2));
+    assert_eq!(config::parse_opengl("4.5"), Ok(OpenGL::V4_5));
+    assert!(config::parse_opengl("3.0").is_err());
+    assert!(config::parse_opengl("").is_err());
+}
+
+#[test]
+fn parses_display_settings() {
+    assert_eq!(parse(&[]).unwrap().display, DisplaySettings::default());
+
+    let display = parse(&["--fullscreen", "--no-vsync", "--msaa", "4", "--opengl", "2.1", "--no-shake"]).unwrap().display;
+    assert_eq!(display, DisplaySettings {
+        fullscreen: true,
+        vsync: false,
+        samples: 4,
+        opengl: Some(OpenGL::V2_1),
+        screen_shake: false,
+    });
+
+    assert!(parse(&["--opengl", "1.0"]).is_err());
+    assert!(parse(&["--opengl"]).is_err());
+    assert!(parse(&["--msaa", "lots"]).is_err());
+}
+
+#[test]
+fn picks_shaders_for_the_context_given() {
+    assert_eq!(config::shader_version(None, "4.6.0 NVIDIA 535.54"), Ok(OpenGL::V3_2));
+    // The window fell back to an older context.
+    assert_eq!(config::shader_version(None, "2.1 Mesa 23.0.4"), Ok(OpenGL::V2_1));
+    assert_eq!(config::shader_version(Some(OpenGL::V2_1), "4.6 (Compatibility Profile) Mesa"), Ok(OpenGL::V2_1));
+    assert!(config::shader_version(Some(OpenGL::V3_3), "3.2.0").is_err());
+    assert!(config::shader_version(None, "1.4").is_err());
+    assert!(config::shader_version(None, "OpenGL ES 3.2 Mesa").unwrap_err().contains("--opengl 2.1"));
+    assert!(config::shader_version(None, "garbage").is_err());
+}
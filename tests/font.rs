@@ -0,0 +1,93 @@
+use basic_game::config::font::{layout, text_width, wrap, Align, VAlign, LINE_SPACING};
+use graphics::character::{Character, CharacterCache};
+use graphics::ImageSize;
+
+struct NoTexture;
+
+impl ImageSize for NoTexture {
+    fn get_size(&self) -> (u32, u32) {
+        (0, 0)
+    }
+}
+
+// Proportional test font: 'i' is a quarter of the font size wide, every other
+// character half of it, and '?' is missing.
+struct TestFont {
+    texture: NoTexture,
+}
+
+impl CharacterCache for TestFont {
+    type Texture = NoTexture;
+    type Error = String;
+
+    fn character(&mut self, size: u32, ch: char) -> Result<Character<'_, NoTexture>, String> {
+        let advance = match ch {
+            '?' => return Err("missing glyph".to_string()),
+            'i' => f64::from(size) / 4.0,
+            _ => f64::from(size) / 2.0,
+        };
+        Ok(Character {
+            offset: [0.0, 0.0],
+            advance_size: [advance, 0.0],
+            atlas_offset: [0.0, 0.0],
+            atlas_size: [0.0, 0.0],
+            texture: &self.texture,
+            is_invalid: false,
+        })
+    }
+}
+
+fn font() -> TestFont {
+    TestFont { texture: NoTexture }
+}
+
+#[test]
+fn width_follows_glyph_advances() {
+    let mut gc = font();
+    assert_eq!(text_width("ab", 10, &mut gc), 10.0);
+    assert_eq!(text_width("ii", 10, &mut gc), 5.0);
+    // Byte length would count 'é' twice.
+    assert_eq!(text_width("é", 10, &mut gc), 5.0);
+    // Missing glyphs fall back to half the size.
+    assert_eq!(text_width("?", 10, &mut gc), 5.0);
+}
+
+#[test]
+fn wrap_breaks_between_words() {
+    let mut gc = font();
+    // Every character is 5 wide, so 6 fit on a 30 wide line.
+    assert_eq!(wrap("one two three", 10, 30.0, &mut gc), vec!["one", "two", "three"]);
+    assert_eq!(wrap("ab cd", 10, 30.0, &mut gc), vec!["ab cd"]);
+    assert_eq!(wrap("ab\ncd", 10, 100.0, &mut gc), vec!["ab", "cd"]);
+}
+
+#[test]
+fn wrap_splits_long_words() {
+    let mut gc = font();
+    assert_eq!(wrap("abcdefgh", 10, 20.0, &mut gc), vec!["abcd", "efgh"]);
+}
+
+#[test]
+fn layout_aligns_lines() {
+    let mut gc = font();
+    let rect = [0.0, 0.0, 100.0, 100.0];
+
+    let left = layout("ab", 10, rect, Align::Left, VAlign::Top, &mut gc);
+    assert_eq!(left[0].pos, [0.0, 10.0]);
+
+    let center = layout("ab", 10, rect, Align::Center, VAlign::Top, &mut gc);
+    assert_eq!(center[0].pos[0], 45.0);
+
+    let right = layout("ab", 10, rect, Align::Right, VAlign::Bottom, &mut gc);
+    assert_eq!(right[0].pos, [90.0, 100.0 - 10.0 * LINE_SPACING + 10.0]);
+}
+
+#[test]
+fn layout_stacks_lines() {
+    let mut gc = font();
+    let lines = layout("ab\ncd", 10, [0.0, 0.0, 100.0, 100.0], Align::Left, VAlign::Middle, &mut gc);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1].pos[1] - lines[0].pos[1], 10.0 * LINE_SPACING);
+    let block = 2.0 * 10.0 * LINE_SPACING;
+    assert_eq!(lines[0].pos[1], (100.0 - block) / 2.0 + 10.0);
+}
@@ -0,0 +1,63 @@
+use basic_game::gym::{self, Action, Env, Observation, ObservationKind, FEATURE_LEN, MAX_PIXELS};
+
+fn run(seed: u64) -> (Vec<Observation>, f64) {
+    let mut env = Env::new(ObservationKind::Features);
+    let mut observations = vec![env.reset(seed)];
+    let mut total = 0.0;
+    let actions = [Action::Left, Action::Fire, Action::Right, Action::Noop];
+    for i in 0..2000 {
+        let (observation, reward, done, _) = env.step(actions[(i / 50) % actions.len()]);
+        observations.push(observation);
+        total += reward;
+        if done {
+            break;
+        }
+    }
+    (observations, total)
+}
+
+#[test]
+fn same_seed_same_episode() {
+    assert_eq!(run(11), run(11));
+}
+
+#[test]
+fn feature_vector_has_fixed_length() {
+    let (observations, _) = run(5);
+    for observation in observations {
+        match observation {
+            Observation::Features { values } => assert_eq!(values.len(), FEATURE_LEN),
+            _ => panic!("expected features"),
+        }
+    }
+}
+
+#[test]
+fn pixel_grid_contains_player() {
+    let mut env = Env::new(ObservationKind::Pixels { width: 20, height: 30 });
+    match env.reset(1) {
+        Observation::Pixels { width, height, data } => {
+            assert_eq!(data.len(), width * height);
+            assert!(data.contains(&255));
+        },
+        _ => panic!("expected pixels"),
+    }
+}
+
+#[test]
+fn rejects_pixel_sizes_that_cannot_be_allocated() {
+    assert!(ObservationKind::Pixels { width: 0, height: 30 }.validate().is_err());
+    assert!(ObservationKind::Pixels { width: usize::MAX, height: 2 }.validate().is_err());
+    assert!(ObservationKind::Pixels { width: MAX_PIXELS, height: MAX_PIXELS }.validate().is_ok());
+
+    let input = concat!(
+        r#"{"cmd":"reset","seed":1,"observation":{"kind":"pixels","width":18446744073709551615,"height":2}}"#, "\n",
+        r#"{"cmd":"reset","seed":1,"observation":{"kind":"pixels","width":4,"height":4}}"#, "\n",
+    );
+    let mut output = Vec::new();
+    gym::run_json_lines(input.as_bytes(), &mut output).unwrap();
+    let lines: Vec<serde_json::Value> = String::from_utf8(output).unwrap()
+        .lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert!(lines[0]["error"].as_str().unwrap().contains("4096"));
+    assert_eq!(lines[1]["observation"]["data"].as_array().unwrap().len(), 16);
+}
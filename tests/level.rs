@@ -0,0 +1,77 @@
+use basic_game::level::{Campaign, Level};
+use basic_game::world::{GameStatus, World};
+use piston::window::Size;
+
+fn world_with(levels: &[&str]) -> World {
+    let levels = levels.iter().map(|l| Level::parse(l).unwrap()).collect();
+    let mut world = World::with_seed(Size { width: 400.0, height: 600.0 }, 1);
+    world.campaign = Some(Campaign::new(levels).unwrap());
+    world
+}
+
+#[test]
+fn plays_levels_in_order_then_wins() {
+    let mut world = world_with(&[
+        r#"{ "name": "one", "waves": [ { "type": "spawn", "at": 10, "column": 0 } ] }"#,
+        r#"{ "name": "two", "waves": [
+            { "type": "spawn", "at": 10, "x": 200, "formation": "line", "count": 3, "spacing": 50 }
+        ] }"#,
+    ]);
+
+    world.update(1.0 / 120.0);
+    assert_eq!(world.campaign.as_ref().unwrap().level().unwrap().name, "one");
+
+    let mut seen_two = false;
+    while world.game_status == GameStatus::Normal {
+        world.update(1.0 / 120.0);
+        if let Some(level) = world.campaign.as_ref().unwrap().level() {
+            if level.name == "two" && world.enemies.len() == 3 {
+                seen_two = true;
+                let xs: Vec<f64> = world.enemies.iter()
+                    .map(|e| basic_game::models::GameObject::position(e).x).collect();
+                assert_eq!(xs, vec![150.0, 200.0, 250.0]);
+            }
+        }
+        assert!(world.uptime < 10_000);
+    }
+
+    assert!(seen_two);
+    assert_eq!(world.game_status, GameStatus::Win);
+    assert_eq!(world.breaches, 4);
+}
+
+#[test]
+fn repeat_and_column_formation_expand_over_time() {
+    let mut world = world_with(&[r#"{ "name": "r", "waves": [
+        { "type": "repeat", "at": 0, "times": 3, "every": 100, "waves": [
+            { "type": "spawn", "at": 5, "column": 3, "formation": "column", "count": 2, "spacing": 20 }
+        ] }
+    ] }"#]);
+
+    let mut spawned = 0;
+    let mut last = 0;
+    for _ in 0..400 {
+        world.update(1.0 / 120.0);
+        let alive = world.enemies.len();
+        if alive > last {
+            spawned += alive - last;
+        }
+        last = alive;
+    }
+    assert_eq!(spawned, 6);
+}
+
+#[test]
+fn rejects_unknown_column() {
+    let level = Level::parse(r#"{ "name": "bad", "waves": [ { "type": "spawn", "at": 1, "column": 42 } ] }"#).unwrap();
+    let world = World::with_seed(Size { width: 400.0, height: 600.0 }, 1);
+    assert!(Campaign::new(vec![level]).unwrap().validate(&world).is_err());
+    assert!(Level::parse(r#"{ "name": "bad", "waves": [ { "type": "teleport" } ] }"#).is_err());
+}
+
+#[test]
+fn bundled_levels_parse() {
+    for entry in std::fs::read_dir("src/assets/levels").unwrap() {
+        Level::load(entry.unwrap().path()).unwrap();
+    }
+}
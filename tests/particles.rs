@@ -0,0 +1,71 @@
+use basic_game::particles::{ParticleConfig, ParticleSystem};
+
+fn config() -> ParticleConfig {
+    ParticleConfig {
+        lifetime: (10, 10),
+        speed: (1.0, 1.0),
+        direction: 0.0,
+        spread: 0.0,
+        gravity: [0.0, 0.5],
+        color: ([1.0, 1.0, 1.0, 1.0], [1.0, 1.0, 1.0, 0.0]),
+        size: (4.0, 2.0),
+    }
+}
+
+#[test]
+fn particles_move_and_expire() {
+    let mut system = ParticleSystem::new(1);
+    system.burst([0.0, 0.0], 3, &config());
+    assert_eq!(system.particles().len(), 3);
+
+    for _ in 0..5 {
+        system.update();
+    }
+    let particle = system.particles()[0];
+    assert!((particle.pos[0] - 5.0).abs() < 1e-9);
+    // Gravity adds up: 0.5 + 1.0 + ... + 2.5.
+    assert!((particle.pos[1] - 7.5).abs() < 1e-9);
+    assert_eq!(particle.size(), 3.0);
+    assert_eq!(particle.color()[3], 0.5);
+
+    for _ in 0..5 {
+        system.update();
+    }
+    assert!(system.particles().is_empty());
+}
+
+#[test]
+fn pool_is_capped() {
+    let mut system = ParticleSystem::with_cap(1, 50);
+    system.burst([0.0, 0.0], 80, &config());
+    assert_eq!(system.particles().len(), 50);
+}
+
+#[test]
+fn emitters_spawn_at_their_rate() {
+    let mut system = ParticleSystem::new(1);
+    system.set_emitter("trail", [1.0, 2.0], 0.5, config());
+    for _ in 0..4 {
+        system.update();
+    }
+    assert_eq!(system.particles().len(), 2);
+
+    system.move_emitter("trail", [9.0, 9.0]);
+    assert_eq!(system.emitter("trail").unwrap().pos, [9.0, 9.0]);
+    system.remove_emitter("trail");
+    for _ in 0..4 {
+        system.update();
+    }
+    assert_eq!(system.particles().len(), 2);
+}
+
+#[test]
+fn same_seed_same_particles() {
+    let run = || {
+        let mut system = ParticleSystem::new(7);
+        system.burst([0.0, 0.0], 20, &ParticleConfig::explosion([1.0; 4]));
+        system.update();
+        system.particles().iter().map(|particle| particle.pos).collect::<Vec<_>>()
+    };
+    assert_eq!(run(), run());
+}
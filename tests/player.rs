@@ -0,0 +1,95 @@
+use basic_game::models::GameObject;
+use basic_game::models::pickup::{Pickup, PickupKind};
+use basic_game::world::World;
+use piston::input::{Button, Key};
+use piston::window::Size;
+
+const SIZE: Size = Size { width: 400.0, height: 600.0 };
+
+fn hold(world: &mut World, key: Key, ticks: u32) {
+    world.input(&Button::Keyboard(key), true);
+    for _ in 0..ticks {
+        world.player.update(1.0 / 120.0, SIZE);
+    }
+}
+
+#[test]
+fn ship_turns_smoothly_towards_its_heading() {
+    let mut world = World::with_seed(SIZE, 1);
+    assert_eq!(world.player.angle, 0.0);
+
+    hold(&mut world, Key::Right, 1);
+    let first = world.player.angle;
+    assert!(first > 0.0 && first < 90.0, "{}", first);
+    assert!(world.player.bank > 0.0);
+
+    hold(&mut world, Key::Right, 60);
+    assert!((world.player.angle - 90.0).abs() < 0.5, "{}", world.player.angle);
+
+    // Left is reached turning back through north, banking the other way.
+    hold(&mut world, Key::Left, 10);
+    assert!(world.player.bank < 0.0);
+}
+
+#[test]
+fn ship_banks_while_moving_sideways() {
+    let mut world = World::with_seed(SIZE, 1);
+    hold(&mut world, Key::Right, 200);
+    // Long done turning, still tilted into the movement.
+    assert!((world.player.angle - 90.0).abs() < 0.01);
+    assert!(world.player.bank > 0.9, "{}", world.player.bank);
+
+    hold(&mut world, Key::Left, 200);
+    assert!(world.player.bank < -0.9, "{}", world.player.bank);
+
+    // Moving straight up levels out.
+    hold(&mut world, Key::Up, 200);
+    assert!(world.player.bank.abs() < 0.01, "{}", world.player.bank);
+}
+
+#[test]
+fn ship_faces_up_again_when_stopping() {
+    let mut world = World::with_seed(SIZE, 1);
+    hold(&mut world, Key::Left, 60);
+    assert!((world.player.angle - 270.0).abs() < 0.5);
+
+    world.input(&Button::Keyboard(Key::Left), false);
+    for _ in 0..60 {
+        world.player.update(1.0 / 120.0, SIZE);
+    }
+    let angle = world.player.angle;
+    assert!(faces_up(angle), "{}", angle);
+}
+
+#[test]
+fn collision_shape_rotates_with_the_ship() {
+    let mut world = World::with_seed(SIZE, 1);
+    let (x, y) = (world.player.pos.x, world.player.pos.y);
+    let radius = world.player.radius();
+
+    // Just below the wide tail while facing up.
+    let behind = Pickup::new(x, y + radius + 4.0, PickupKind::Ammo);
+    assert!(world.player.collides(&behind));
+
+    // Turned to the right the tail points left, the same spot is missed.
+    world.player.angle = 90.0;
+    assert!(!world.player.collides(&behind));
+    let nose = world.player.hull()[0];
+    assert!((nose[0] - (x + radius)).abs() < 1e-9 && (nose[1] - y).abs() < 1e-9);
+}
+
+fn faces_up(angle: f64) -> bool {
+    angle.min(360.0 - angle) < 0.5
+}
+
+#[test]
+fn velocity_follows_the_heading() {
+    let mut world = World::with_seed(SIZE, 1);
+    assert_eq!(world.player.velocity(), [0.0, 0.0]);
+
+    let start = world.player.pos.x;
+    hold(&mut world, Key::Right, 1);
+    let vel = world.player.velocity();
+    assert!(vel[0] > 0.0 && vel[1].abs() < 1e-9);
+    assert!((world.player.pos.x - start - vel[0]).abs() < 1e-9);
+}
@@ -0,0 +1,144 @@
+use std::env;
+
+use basic_game::bot::{self, Bot};
+use basic_game::console::Console;
+use basic_game::director::{Director, DirectorConfig};
+use basic_game::level::{Campaign, Level};
+use basic_game::replay::{self, Recorder, Replay};
+use basic_game::world::World;
+use piston::window::Size;
+
+const SIZE: Size = Size { width: 400.0, height: 600.0 };
+
+// Let the bot play `ticks` updates while recording.
+fn record(seed: u64, ticks: u64) -> (World, Replay) {
+    record_from(World::with_seed(SIZE, seed), ticks)
+}
+
+fn record_from(mut world: World, ticks: u64) -> (World, Replay) {
+    let mut recorder = Recorder::new(&world).unwrap();
+    let mut bot = Bot::new();
+    for _ in 0..ticks {
+        for (button, press) in bot.act(&world) {
+            recorder.input(&button, press);
+            world.input(&button, press);
+        }
+        world.update(1.0 / 120.0);
+        recorder.tick(&world);
+    }
+    (world, recorder.replay)
+}
+
+#[test]
+fn playback_reproduces_the_game() {
+    let (world, replay) = record(5, 3000);
+    assert!(!replay.inputs.is_empty());
+    assert_eq!(replay.ticks, 3000);
+
+    let played = replay::run(&replay).unwrap();
+    assert_eq!(played.uptime, world.uptime);
+    assert_eq!(played.score, world.score);
+    assert_eq!(played.ammo, world.ammo);
+    assert_eq!(played.player.health, world.player.health);
+    assert_eq!((played.player.pos.x, played.player.pos.y), (world.player.pos.x, world.player.pos.y));
+    assert_eq!(played.enemies.len(), world.enemies.len());
+}
+
+#[test]
+fn saves_and_loads() {
+    let (_, replay) = record(9, 500);
+    let path = env::temp_dir().join(format!("replay_{}.json", std::process::id()));
+    replay.save(&path).unwrap();
+    assert_eq!(Replay::load(&path).unwrap(), replay);
+
+    // The console restarts a live world and feeds it the replay.
+    let mut world = World::with_seed(Size { width: 300.0, height: 300.0 }, 1);
+    world.score = 40;
+    let mut console = Console::new();
+    console.run(&format!("replay {}", path.display()), &mut world).unwrap();
+    assert_eq!((world.score, world.seed, world.size), (0, 9, SIZE));
+    console.run("step 500", &mut world).unwrap();
+    assert!(console.playback.is_none());
+    assert_eq!(console.log.last().map(String::as_str), Some("Replay finished"));
+
+    let expected = replay::run(&replay).unwrap();
+    assert_eq!((world.uptime, world.score), (expected.uptime, expected.score));
+    assert_eq!(world.player.pos.x, expected.player.pos.x);
+
+    let mut broken = replay.clone();
+    broken.start["world"]["rules"]["difficulty_interval"] = serde_json::json!(0);
+    broken.save(&path).unwrap();
+    assert!(Replay::load(&path).is_err());
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(Replay::load(&path).is_err());
+}
+
+#[test]
+fn checksums_match_on_playback() {
+    let (world, replay) = record(11, 1500);
+    assert_eq!(replay.checksums.len(), 1500);
+    assert_eq!(replay.checksums.last(), Some(&world.checksum()));
+
+    let played = replay::verify(&replay).unwrap();
+    assert_eq!(played.checksum(), world.checksum());
+}
+
+#[test]
+fn reports_the_first_tick_that_differs() {
+    let (_, mut replay) = record(12, 800);
+    replay.checksums[400] ^= 1;
+    replay.checksums[600] ^= 1;
+    assert_eq!(replay::verify(&replay).err().as_deref(), Some("Replay diverges at tick 400"));
+
+    // The console says so while playing too.
+    let mut world = replay.world().unwrap();
+    let mut console = Console::new();
+    console.playback = Some(replay::Playback::new(replay));
+    console.run("step 800", &mut world).unwrap();
+    assert!(console.log.contains(&"Replay diverges at tick 400".to_string()));
+
+    // Without checksums there is nothing to check.
+    let (_, mut replay) = record(12, 10);
+    replay.checksums.clear();
+    assert!(replay::verify(&replay).is_err());
+}
+
+#[test]
+fn checksum_follows_the_state() {
+    let mut world = World::with_seed(SIZE, 13);
+    let start = world.checksum();
+    assert_eq!(World::with_seed(SIZE, 13).checksum(), start);
+    assert_ne!(World::with_seed(SIZE, 14).checksum(), start);
+
+    world.player.pos.x += 1e-9;
+    assert_ne!(world.checksum(), start);
+    world.player.pos.x -= 1e-9;
+    world.update(1.0 / 120.0);
+    assert_ne!(world.checksum(), start);
+}
+
+#[test]
+fn replays_start_from_the_recorded_world() {
+    // A game already under way, with levels.
+    let level = Level::parse(r#"{
+        "name": "Recorded",
+        "waves": [
+            { "type": "spawn", "at": 10, "kind": "fast", "column": 2, "formation": "v", "count": 3 },
+            { "type": "spawn", "at": 400, "kind": "heavy", "column": 4 }
+        ]
+    }"#).unwrap();
+    let mut world = World::with_seed(SIZE, 21);
+    world.campaign = Some(Campaign::new(vec![level]).unwrap());
+    bot::soak(&mut world, 200).unwrap();
+    let (world, replay) = record_from(world, 1000);
+    assert_eq!(replay::verify(&replay).unwrap().checksum(), world.checksum());
+
+    // Endless mode with the director.
+    let mut world = World::with_seed(SIZE, 22);
+    world.director = Some(Director::new(DirectorConfig::default()));
+    let (world, replay) = record_from(world, 2000);
+    let played = replay::verify(&replay).unwrap();
+    assert_eq!(played.checksum(), world.checksum());
+    assert!(played.director.is_some());
+}
@@ -0,0 +1,161 @@
+use std::env;
+
+use basic_game::bot::Bot;
+use basic_game::director::{Director, DirectorConfig};
+use basic_game::level::{Campaign, Level};
+use basic_game::models::pickup::{Pickup, PickupKind};
+use basic_game::save::{self, Migration, Slots, SAVE_VERSION};
+use basic_game::world::{GameStatus, World};
+use piston::window::Size;
+use rand::Rng;
+use serde_json::{json, Value};
+
+const SIZE: Size = Size { width: 400.0, height: 600.0 };
+
+fn play(world: &mut World, bot: &mut Bot, ticks: u64) {
+    for _ in 0..ticks {
+        for (button, press) in bot.act(world) {
+            world.input(&button, press);
+        }
+        world.update(1.0 / 120.0);
+    }
+}
+
+// Save after `before` updates, then check the loaded copy carries on exactly
+// like the original for `after` more.
+fn assert_round_trip(mut world: World, before: u64, after: u64) {
+    let mut bot = Bot::new();
+    play(&mut world, &mut bot, before);
+
+    let text = save::to_json(&world).unwrap();
+    let mut loaded = save::from_json(&text).unwrap();
+    assert_eq!(save::to_json(&loaded).unwrap(), text);
+
+    let mut loaded_bot = Bot::new();
+    play(&mut world, &mut bot, after);
+    play(&mut loaded, &mut loaded_bot, after);
+    assert_eq!(save::to_json(&loaded).unwrap(), save::to_json(&world).unwrap());
+    assert_eq!(loaded.rng.gen::<u64>(), world.rng.gen::<u64>());
+}
+
+#[test]
+fn endless_game_round_trips() {
+    let mut world = World::with_seed(SIZE, 3);
+    world.pickups.push(Pickup::new(200.0, 10.0, PickupKind::Health));
+    assert_round_trip(world, 2000, 2000);
+}
+
+#[test]
+fn director_game_round_trips() {
+    let mut world = World::with_seed(SIZE, 4);
+    world.director = Some(Director::new(DirectorConfig::default()));
+    assert_round_trip(world, 1500, 1500);
+}
+
+#[test]
+fn campaign_round_trips() {
+    let level = Level::parse(r#"{
+        "name": "Saved",
+        "waves": [
+            { "type": "spawn", "at": 10, "kind": "fast", "column": 2, "formation": "v", "count": 3 },
+            { "type": "repeat", "at": 200, "times": 4, "every": 150, "waves": [
+                { "type": "random", "at": 0, "chance": 0.5, "kinds": ["basic", "heavy"] }
+            ] }
+        ]
+    }"#).unwrap();
+    let mut world = World::with_seed(SIZE, 5);
+    world.campaign = Some(Campaign::new(vec![level]).unwrap());
+    assert_round_trip(world, 300, 900);
+}
+
+#[test]
+fn finished_game_round_trips() {
+    let mut world = World::with_seed(SIZE, 6);
+    world.player.health = 1.0;
+    let mut bot = Bot::new();
+    while world.game_status == GameStatus::Normal {
+        play(&mut world, &mut bot, 1);
+    }
+    let loaded = save::from_json(&save::to_json(&world).unwrap()).unwrap();
+    assert_eq!(loaded.game_status, GameStatus::Died);
+    assert_eq!(loaded.score, world.score);
+}
+
+fn load_error(text: &str) -> String {
+    match save::from_json(text) {
+        Ok(_) => panic!("loaded a broken save"),
+        Err(msg) => msg,
+    }
+}
+
+#[test]
+fn rejects_unknown_versions() {
+    let world = World::with_seed(SIZE, 1);
+    let mut value: Value = serde_json::from_str(&save::to_json(&world).unwrap()).unwrap();
+    assert_eq!(value["version"], json!(SAVE_VERSION));
+
+    value["version"] = json!(SAVE_VERSION + 1);
+    assert!(load_error(&value.to_string()).contains("not supported"));
+    value.as_object_mut().unwrap().remove("version");
+    assert!(save::from_json(&value.to_string()).is_err());
+    assert!(save::from_json("{").is_err());
+}
+
+#[test]
+fn rejects_rules_the_game_cannot_run() {
+    let world = World::with_seed(SIZE, 1);
+    let value: Value = serde_json::from_str(&save::to_json(&world).unwrap()).unwrap();
+
+    let mut zero = value.clone();
+    zero["world"]["rules"]["min_spawn_interval"] = json!(0);
+    assert!(load_error(&zero.to_string()).contains("min_spawn_interval"));
+
+    let mut inverted = value;
+    inverted["world"]["rules"]["min_spawn_interval"] = json!(600);
+    assert!(load_error(&inverted.to_string()).contains("enemy_spawn_cooldown"));
+}
+
+#[test]
+fn migrations_run_in_order() {
+    fn rename_points(mut value: Value) -> Result<Value, String> {
+        let points = value["world"].as_object_mut().unwrap().remove("points").ok_or("no points")?;
+        value["world"]["score"] = points;
+        Ok(value)
+    }
+    fn double_score(mut value: Value) -> Result<Value, String> {
+        value["world"]["score"] = json!(value["world"]["score"].as_u64().unwrap() * 2);
+        Ok(value)
+    }
+    let migrations: [Migration; 2] = [rename_points, double_score];
+
+    let old = json!({ "version": 1, "world": { "points": 4 } });
+    let upgraded = save::upgrade(old, &migrations).unwrap();
+    assert_eq!(upgraded, json!({ "version": 3, "world": { "score": 8 } }));
+
+    let middle = json!({ "version": 2, "world": { "score": 4 } });
+    assert_eq!(save::upgrade(middle, &migrations).unwrap()["world"]["score"], json!(8));
+
+    let broken = json!({ "version": 1, "world": {} });
+    assert!(save::upgrade(broken, &migrations).unwrap_err().contains("version 1"));
+}
+
+#[test]
+fn slots_are_separate() {
+    let dir = env::temp_dir().join(format!("save_slots_{}", std::process::id()));
+    let slots = Slots::new(&dir);
+    let mut first = World::with_seed(SIZE, 1);
+    first.score = 11;
+    let mut second = World::with_seed(SIZE, 2);
+    second.score = 22;
+
+    assert!(!slots.exists(0));
+    slots.save(0, &first).unwrap();
+    slots.save(1, &second).unwrap();
+    assert_eq!(slots.load(0).unwrap().score, 11);
+    assert_eq!(slots.load(1).unwrap().score, 22);
+    assert!(slots.load(2).is_err());
+
+    slots.delete(0).unwrap();
+    assert!(!slots.exists(0) && slots.exists(1));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,86 @@
+use std::{env, fs};
+
+use basic_game::bot;
+use basic_game::save;
+use basic_game::snapshot::{self, Snapshots};
+use basic_game::world::{GameStatus, World};
+use piston::window::Size;
+use serde_json::Value;
+
+const SIZE: Size = Size { width: 400.0, height: 600.0 };
+
+fn dir(name: &str) -> std::path::PathBuf {
+    let dir = env::temp_dir().join(format!("basic_game_snapshot_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn snapshot_holds_the_whole_world() {
+    let mut world = World::with_seed(SIZE, 5);
+    bot::soak(&mut world, 600).unwrap();
+
+    let value: Value = serde_json::from_str(&snapshot::to_json(&world, "test").unwrap()).unwrap();
+    assert_eq!(value["reason"], "test");
+    let saved = &value["world"];
+    assert_eq!(saved["uptime"], world.uptime);
+    assert_eq!(saved["score"], world.score);
+    assert_eq!(saved["ammo"], world.ammo);
+    assert_eq!(saved["game_status"], format!("{:?}", world.game_status));
+    assert_eq!(saved["player"]["health"], world.player.health);
+    assert_eq!(saved["enemies"].as_array().unwrap().len(), world.enemies.len());
+    assert_eq!(saved["bullets"].as_array().unwrap().len(), world.bullets.len());
+}
+
+#[test]
+fn snapshots_load_into_a_headless_world() {
+    let mut world = World::with_seed(SIZE, 6);
+    bot::soak(&mut world, 900).unwrap();
+
+    let snapshots = Snapshots::new(dir("load"), None);
+    let path = snapshots.take(&world, "key").unwrap();
+    assert!(path.ends_with(format!("snapshot_{}_key.json", world.uptime)));
+
+    let mut loaded = snapshot::load(&path).unwrap();
+    assert_eq!(save::to_json(&loaded).unwrap(), save::to_json(&world).unwrap());
+    bot::soak(&mut loaded, 300).unwrap();
+    bot::soak(&mut world, 300).unwrap();
+    assert_eq!(save::to_json(&loaded).unwrap(), save::to_json(&world).unwrap());
+}
+
+#[test]
+fn snapshots_every_n_updates() {
+    let dir = dir("every");
+    let mut snapshots = Snapshots::new(&dir, Some(100));
+    let mut world = World::with_seed(SIZE, 7);
+    let mut written = Vec::new();
+    bot::soak_with(&mut world, 350, |world| written.extend(snapshots.tick(world).unwrap())).unwrap();
+
+    assert_eq!(written.len(), 3);
+    assert!(written[0].ends_with("snapshot_100_tick.json"));
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+}
+
+#[test]
+fn finished_games_are_snapshotted_once() {
+    let dir = dir("finished");
+    let mut snapshots = Snapshots::new(&dir, Some(10));
+    let mut world = World::with_seed(SIZE, 8);
+    world.uptime = 40;
+    world.game_status = GameStatus::Died;
+    // The world stands still now, every update sees the same uptime.
+    let mut written = 0;
+    for _ in 0..5 {
+        world.update(1.0 / 120.0);
+        assert_eq!(world.uptime, 40);
+        written += snapshots.tick(&world).unwrap().into_iter().count();
+    }
+    assert_eq!(written, 1);
+
+    world.reset();
+    for _ in 0..10 {
+        world.update(1.0 / 120.0);
+        snapshots.tick(&world).unwrap();
+    }
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+}
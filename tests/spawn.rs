@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+use basic_game::models::GameObject;
+use basic_game::spawn::{Edge, RelativePoint, SpawnLayout};
+use basic_game::world::World;
+use piston::window::Size;
+
+fn size(width: f64, height: f64) -> Size {
+    Size { width, height }
+}
+
+#[test]
+fn default_lanes_split_width_evenly() {
+    for width in [400.0, 333.0, 1024.0] {
+        let points = SpawnLayout::default().points(size(width, 600.0));
+        assert_eq!(points.len(), 9);
+        for (i, point) in points.iter().enumerate() {
+            let expected = width * (i + 1) as f64 / 10.0;
+            assert!((point.x - expected).abs() < 1e-9, "lane {} at {} on width {}", i, point.x, width);
+            assert_eq!(point.y, 0.0);
+            assert_eq!(point.from, Edge::Top);
+        }
+    }
+}
+
+#[test]
+fn every_lane_gets_enemies() {
+    let mut world = World::with_seed(size(400.0, 600.0), 4);
+    world.set_spawn_layout(SpawnLayout::Lanes { count: 12 });
+    world.player.health = f64::MAX;
+
+    let mut seen = HashSet::new();
+    for _ in 0..20_000 {
+        world.update(1.0 / 120.0);
+        for enemy in world.enemies.iter() {
+            if enemy.position().y <= 1.0 {
+                seen.insert((enemy.position().x * 1000.0) as i64);
+            }
+        }
+    }
+
+    let lanes: HashSet<i64> = world.spawn_points.iter().map(|p| (p.x * 1000.0) as i64).collect();
+    assert_eq!(seen, lanes);
+}
+
+#[test]
+fn edge_points_head_into_the_playfield() {
+    let layout = SpawnLayout::parse("edges:2").unwrap();
+    let points = layout.points(size(400.0, 600.0));
+    assert_eq!(points.len(), 8);
+
+    let mut world = World::with_seed(size(400.0, 600.0), 1);
+    world.set_spawn_layout(layout);
+    world.player.health = f64::MAX;
+    let mut sides = HashSet::new();
+    for _ in 0..5_000 {
+        world.update(1.0 / 120.0);
+        for enemy in world.enemies.iter() {
+            let pos = enemy.position();
+            assert!((0.0..=400.0).contains(&pos.x) && (0.0..=600.0).contains(&pos.y));
+            sides.insert(format!("{:?}", enemy.heading()));
+        }
+    }
+    assert_eq!(sides.len(), 4);
+}
+
+#[test]
+fn points_follow_resize() {
+    let mut world = World::with_seed(size(400.0, 600.0), 1);
+    world.set_spawn_layout(SpawnLayout::Points { points: vec![
+        RelativePoint { x: 0.5, y: 0.0, from: Edge::Top },
+        RelativePoint { x: 1.0, y: 0.25, from: Edge::Right },
+    ] });
+    assert_eq!((world.spawn_points[1].x, world.spawn_points[1].y), (400.0, 150.0));
+
+    world.resize(size(800.0, 1000.0));
+    assert_eq!((world.spawn_points[0].x, world.spawn_points[0].y), (400.0, 0.0));
+    assert_eq!((world.spawn_points[1].x, world.spawn_points[1].y), (800.0, 250.0));
+}
+
+#[test]
+fn rejects_bad_specs() {
+    assert!(SpawnLayout::parse("lanes:0").is_err());
+    assert!(SpawnLayout::parse("edges:3:top,middle").is_err());
+    assert!(SpawnLayout::parse("spiral:3").is_err());
+    assert_eq!(SpawnLayout::parse("edges:1:left,right").unwrap().points(size(10.0, 10.0)).len(), 2);
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use basic_game::config::assets::AssetManager;
+use basic_game::config::sprite::{Atlas, SpriteDef};
+
+fn atlases() -> BTreeMap<String, Atlas> {
+    let mut frames = BTreeMap::new();
+    frames.insert("player".to_string(), [0.0, 0.0, 32.0, 32.0]);
+    let mut atlases = BTreeMap::new();
+    atlases.insert("ships".to_string(), Atlas { texture: "ships_png".to_string(), frames });
+    atlases
+}
+
+#[test]
+fn whole_texture_sprite() {
+    let def = SpriteDef { texture: Some("bullet".to_string()), rotation: 90.0, ..SpriteDef::default() };
+    let sprite = def.resolve(&atlases()).unwrap();
+    assert_eq!(sprite.texture, "bullet");
+    assert_eq!(sprite.frame, None);
+    assert_eq!(sprite.tint, [1.0; 4]);
+    assert_eq!(sprite.rotation, 90.0);
+}
+
+#[test]
+fn atlas_frame_sprite() {
+    let def = SpriteDef {
+        atlas: Some("ships".to_string()),
+        frame: Some("player".to_string()),
+        tint: Some([1.0, 0.0, 0.0, 1.0]),
+        ..SpriteDef::default()
+    };
+    let sprite = def.resolve(&atlases()).unwrap();
+    assert_eq!(sprite.texture, "ships_png");
+    assert_eq!(sprite.frame, Some([0.0, 0.0, 32.0, 32.0]));
+    assert_eq!(sprite.tint, [1.0, 0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn broken_sprites_are_rejected() {
+    let unknown_frame = SpriteDef { atlas: Some("ships".to_string()), frame: Some("enemy".to_string()), ..SpriteDef::default() };
+    assert!(unknown_frame.resolve(&atlases()).is_err());
+    let both = SpriteDef { texture: Some("a".to_string()), atlas: Some("ships".to_string()), ..SpriteDef::default() };
+    assert!(both.resolve(&atlases()).is_err());
+    assert!(SpriteDef::default().resolve(&atlases()).is_err());
+}
+
+#[test]
+fn manifest_sprites_are_checked() {
+    let root = std::env::temp_dir().join(format!("basic_game_sprites_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("ships.png"), b"").unwrap();
+    fs::write(root.join("manifest.json"), r#"{
+        "textures": { "ships": "ships.png" },
+        "atlases": { "ships": { "texture": "ships", "frames": { "player": [0, 0, 32, 32] } } },
+        "sprites": {
+            "player": { "atlas": "ships", "frame": "player" },
+            "bullet": { "texture": "bullets" }
+        }
+    }"#).unwrap();
+
+    let manager = AssetManager::open(&root).unwrap();
+    assert_eq!(manager.sprite("player").unwrap().frame, Some([0.0, 0.0, 32.0, 32.0]));
+    assert!(manager.sprite("bullet").is_none());
+    let err = manager.check().unwrap_err();
+    assert!(err.contains("sprite 'bullet'"), "{}", err);
+}
@@ -0,0 +1,76 @@
+use basic_game::audio::{Sfx, Sound, SAMPLE_RATE};
+use basic_game::synth::{self, Preset, SynthParams, Wave};
+
+const PRESETS: [Preset; 5] = [Preset::Shoot, Preset::Explosion, Preset::Pickup, Preset::Hurt, Preset::PowerDown];
+
+fn crossings(samples: &[f32]) -> usize {
+    samples.windows(2).filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0)).count()
+}
+
+#[test]
+fn same_seed_same_sound() {
+    for preset in PRESETS {
+        let a = SynthParams::preset(preset, 3).render(3);
+        let b = SynthParams::preset(preset, 3).render(3);
+        let c = SynthParams::preset(preset, 4).render(4);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(!a.samples.is_empty());
+        assert!(a.samples.iter().all(|sample| (-1.0..=1.0).contains(sample)));
+        assert!(a.duration() <= 4.0);
+    }
+}
+
+#[test]
+fn every_effect_has_a_default() {
+    let sounds: Vec<Sound> = Sfx::ALL.iter().map(|sfx| synth::sfx_sound(*sfx, synth::DEFAULT_SEED)).collect();
+    for (i, sound) in sounds.iter().enumerate() {
+        assert!(sound.samples.iter().any(|sample| *sample != 0.0));
+        assert!(sounds[i + 1..].iter().all(|other| other != sound));
+    }
+}
+
+#[test]
+fn slide_lowers_the_pitch() {
+    let params = SynthParams {
+        wave: Wave::Saw,
+        freq: 800.0,
+        slide: -2.0,
+        sustain: 0.5,
+        decay: 0.0,
+        ..SynthParams::default()
+    };
+    let sound = params.render(0);
+    let half = sound.samples.len() / 2;
+    assert!(crossings(&sound.samples[..half]) > crossings(&sound.samples[half..]));
+
+    // A falling slide ends the sound at the lowest frequency.
+    let cut = SynthParams { min_freq: 500.0, ..params }.render(0);
+    assert!(cut.samples.len() < sound.samples.len());
+}
+
+#[test]
+fn envelope_shapes_the_volume() {
+    let params = SynthParams {
+        wave: Wave::Square,
+        attack: 0.1,
+        sustain: 0.1,
+        decay: 0.1,
+        volume: 1.0,
+        ..SynthParams::default()
+    };
+    let sound = params.render(0);
+    let at = |t: f64| sound.samples[(t * f64::from(SAMPLE_RATE)) as usize].abs();
+    assert_eq!(sound.samples.len(), (0.3 * f64::from(SAMPLE_RATE)) as usize);
+    assert!(at(0.01) < 0.2);
+    assert!((at(0.15) - 1.0).abs() < 0.001);
+    assert!(at(0.29) < 0.2);
+}
+
+#[test]
+fn exports_to_wav() {
+    let sound = synth::sfx_sound(Sfx::Explosion, 11);
+    let decoded = Sound::from_wav(&sound.to_wav()).unwrap();
+    assert_eq!(decoded.rate, SAMPLE_RATE);
+    assert_eq!(decoded.samples.len(), sound.samples.len());
+}
@@ -0,0 +1,55 @@
+use basic_game::save::{Slots, SLOTS};
+use basic_game::title::{MenuItem, TitleScreen};
+use basic_game::world::World;
+use piston::input::Key;
+use piston::window::Size;
+
+fn slots(name: &str) -> Slots {
+    let dir = std::env::temp_dir().join(format!("basic_game_title_{}_{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    Slots::new(dir)
+}
+
+#[test]
+fn title_offers_new_game_and_continue() {
+    let mut title = TitleScreen::new(false, &slots("fresh"));
+    assert_eq!(title.items(), vec![MenuItem::NewGame, MenuItem::Continue, MenuItem::Quit]);
+    assert_eq!(title.input(Key::Return), Some(MenuItem::NewGame));
+
+    // Nothing to continue from yet.
+    title.input(Key::Down);
+    assert_eq!(title.input(Key::Return), None);
+    assert!(title.message.is_some());
+
+    title.input(Key::Down);
+    title.input(Key::Down);
+    assert_eq!(title.selected, 0);
+    title.input(Key::Up);
+    assert_eq!(title.input(Key::Return), Some(MenuItem::Quit));
+}
+
+#[test]
+fn pause_menu_can_save() {
+    let slots = slots("pause");
+    let world = World::with_seed(Size { width: 400.0, height: 600.0 }, 1);
+    slots.save(1, &world).unwrap();
+
+    let mut title = TitleScreen::new(true, &slots);
+    assert_eq!(title.items(), vec![MenuItem::Resume, MenuItem::NewGame, MenuItem::Continue, MenuItem::SaveAndQuit, MenuItem::Quit]);
+    // Opens on the slot that has a save.
+    assert_eq!(title.slot, 1);
+    assert!(title.lines()[2].contains("Continue slot 2"));
+
+    title.input(Key::Down);
+    title.input(Key::Down);
+    assert_eq!(title.input(Key::Return), Some(MenuItem::Continue));
+
+    for _ in 0..SLOTS {
+        title.input(Key::Right);
+    }
+    assert_eq!(title.slot, 1);
+    title.input(Key::Left);
+    title.input(Key::Down);
+    assert_eq!(title.input(Key::Return), Some(MenuItem::SaveAndQuit));
+    assert!(title.lines()[3].starts_with("> Save to slot 1"));
+}
@@ -0,0 +1,67 @@
+use basic_game::tui::{Color, Screen};
+use basic_game::world::{GameStatus, World};
+use piston::input::{Button, Key};
+use piston::window::Size;
+
+fn world() -> World {
+    World::new(Size { width: 400.0, height: 600.0 })
+}
+
+#[test]
+fn draws_player_and_hud() {
+    let world = world();
+    let mut screen = Screen::new(40, 22);
+    screen.draw_world(&world);
+
+    assert!(screen.row_text(0).starts_with("Score: 0"));
+    assert!(screen.row_text(0).trim_end().ends_with("Health: 1000.0"));
+    assert!(screen.row_text(21).trim_end().ends_with("Bullets: 20"));
+
+    // The player starts at (width / 2, height / 1.25).
+    let (col, row) = screen.to_cell(&world, 200.0, 480.0).unwrap();
+    let cell = screen.get(col, row).unwrap();
+    assert_eq!(cell.ch, 'A');
+    assert_eq!(cell.color, Color::Red);
+}
+
+#[test]
+fn playfield_scales_to_grid() {
+    let world = world();
+    for (cols, rows) in [(20, 10), (80, 24), (200, 60)] {
+        let screen = Screen::new(cols, rows);
+        assert_eq!(screen.to_cell(&world, 0.0, 0.0), Some((0, 1)));
+        assert_eq!(screen.to_cell(&world, 399.9, 599.9), Some((cols - 1, rows - 2)));
+        assert_eq!(screen.to_cell(&world, 400.0, 0.0), None);
+    }
+}
+
+#[test]
+fn input_moves_player_on_screen() {
+    let mut world = world();
+    let mut screen = Screen::new(40, 22);
+    let start = world.player.pos.x;
+
+    world.input(&Button::Keyboard(Key::Left), true);
+    for _ in 0..60 {
+        world.update(1.0 / 120.0);
+    }
+    world.input(&Button::Keyboard(Key::Left), false);
+    assert!(world.player.pos.x < start);
+
+    screen.draw_world(&world);
+    let (col, row) = screen.to_cell(&world, world.player.pos.x, world.player.pos.y).unwrap();
+    assert_eq!(screen.get(col, row).unwrap().ch, 'A');
+}
+
+#[test]
+fn game_over_screen() {
+    let mut world = world();
+    world.player.health = 0.0;
+    world.update(1.0 / 120.0);
+    assert_eq!(world.game_status, GameStatus::Died);
+
+    let mut screen = Screen::new(40, 20);
+    screen.draw_world(&world);
+    assert_eq!(screen.row_text(8).trim(), "DEAD");
+    assert_eq!(screen.row_text(10).trim(), "Score: 0");
+}
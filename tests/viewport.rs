@@ -0,0 +1,34 @@
+use basic_game::config::viewport::Letterbox;
+use piston::window::Size;
+
+const PLAYFIELD: Size = Size { width: 400.0, height: 600.0 };
+
+#[test]
+fn same_aspect_ratio_has_no_bars() {
+    let letterbox = Letterbox::fit(PLAYFIELD, [800.0, 1200.0]);
+    assert_eq!(letterbox.scale, 2.0);
+    assert_eq!(letterbox.offset, [0.0, 0.0]);
+    for bar in letterbox.bars() {
+        assert!(bar[2] * bar[3] == 0.0);
+    }
+}
+
+#[test]
+fn wide_window_is_pillarboxed() {
+    let letterbox = Letterbox::fit(PLAYFIELD, [1000.0, 600.0]);
+    assert_eq!(letterbox.scale, 1.0);
+    assert_eq!(letterbox.offset, [300.0, 0.0]);
+    assert_eq!(letterbox.to_virtual([300.0, 0.0]), [0.0, 0.0]);
+    assert_eq!(letterbox.to_virtual([700.0, 600.0]), [400.0, 600.0]);
+    assert_eq!(letterbox.bars()[2], [0.0, 0.0, 300.0, 600.0]);
+    assert_eq!(letterbox.bars()[3], [700.0, 0.0, 300.0, 600.0]);
+}
+
+#[test]
+fn tall_window_is_letterboxed() {
+    let letterbox = Letterbox::fit(PLAYFIELD, [200.0, 500.0]);
+    assert_eq!(letterbox.scale, 0.5);
+    assert_eq!(letterbox.offset, [0.0, 100.0]);
+    assert_eq!(letterbox.bars()[0], [0.0, 0.0, 200.0, 100.0]);
+    assert_eq!(letterbox.bars()[1], [0.0, 400.0, 200.0, 100.0]);
+}